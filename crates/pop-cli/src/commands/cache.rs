@@ -0,0 +1,165 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use crate::style::{format_bytes, style, Theme};
+use clap::{Args, Subcommand};
+use cliclack::{clear_screen, confirm, intro, log, outro, outro_cancel, set_theme};
+use pop_parachains::{Cache, CachedBinary};
+use std::time::SystemTime;
+
+#[derive(Args)]
+#[command(args_conflicts_with_subcommands = true)]
+pub(crate) struct CacheArgs {
+	#[command(subcommand)]
+	pub(crate) command: CacheCommands,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum CacheCommands {
+	/// List the binaries within the cache.
+	#[clap(alias = "l")]
+	List,
+	/// Remove all but the most recent versions of each cached binary.
+	Prune(PruneCommand),
+	/// Remove all cached binaries.
+	Clean(CleanCommand),
+	/// Show the location of the cache.
+	Path,
+}
+
+#[derive(Args)]
+pub(crate) struct PruneCommand {
+	/// The number of versions of each binary to keep.
+	#[arg(short, long, default_value_t = 1)]
+	keep: usize,
+}
+
+#[derive(Args)]
+pub(crate) struct CleanCommand {
+	/// Remove all cached binaries without confirmation.
+	#[arg(short, long, action)]
+	force: bool,
+}
+
+impl CacheArgs {
+	pub(crate) fn execute(&self) -> anyhow::Result<()> {
+		let cache = Cache::new(&crate::cache()?);
+		if let CacheCommands::Path = self.command {
+			println!("{}", cache.path().display());
+			return Ok(());
+		}
+		clear_screen()?;
+		intro(format!("{}: Manage cached binaries", style(" Pop CLI ").black().on_magenta()))?;
+		set_theme(Theme);
+		match &self.command {
+			CacheCommands::List => {
+				let binaries = cache.binaries()?;
+				for binary in &binaries {
+					log::info(describe(binary))?;
+				}
+				let size: u64 = binaries.iter().map(|b| b.size).sum();
+				outro(format!(
+					"{} cached binaries ({}) at {}",
+					binaries.len(),
+					format_bytes(size),
+					cache.path().display()
+				))?;
+			},
+			CacheCommands::Prune(cmd) => {
+				let removed = cache.prune(cmd.keep)?;
+				report(&removed)?;
+			},
+			CacheCommands::Clean(cmd) => {
+				if !cmd.force &&
+					!confirm(format!(
+						"Would you like to remove all cached binaries from {}?",
+						cache.path().display()
+					))
+					.initial_value(false)
+					.interact()?
+				{
+					outro_cancel("🚫 No cached binaries were removed.")?;
+					return Ok(());
+				}
+				let removed = cache.clean()?;
+				report(&removed)?;
+			},
+			CacheCommands::Path => unreachable!("handled above"),
+		}
+		Ok(())
+	}
+}
+
+// Describes a cached binary, suitable for display.
+fn describe(binary: &CachedBinary) -> String {
+	let mut description = binary.name.clone();
+	if let Some(version) = &binary.version {
+		description.push_str(&format!(" {version}"));
+	}
+	description.push_str(&format!(" ({})", format_bytes(binary.size)));
+	if let Some(last_used) = binary.last_used {
+		description.push_str(&format!(", last used {}", elapsed(last_used)));
+	}
+	if let Some(source) = &binary.source {
+		description.push_str(&format!("\n{source}"));
+	}
+	description
+}
+
+// Reports the binaries removed from the cache.
+fn report(removed: &[CachedBinary]) -> anyhow::Result<()> {
+	for binary in removed {
+		log::info(format!("Removed {}", binary.path.display()))?;
+	}
+	let size: u64 = removed.iter().map(|b| b.size).sum();
+	outro(format!(
+		"✅ Removed {} cached binaries, freeing {}.",
+		removed.len(),
+		format_bytes(size)
+	))?;
+	Ok(())
+}
+
+// Describes the time elapsed since the provided time, relative to now.
+fn elapsed(time: SystemTime) -> String {
+	let seconds = SystemTime::now().duration_since(time).unwrap_or_default().as_secs();
+	let (value, unit) = match seconds {
+		0..=59 => return "just now".into(),
+		60..=3_599 => (seconds / 60, "minute"),
+		3_600..=86_399 => (seconds / 3_600, "hour"),
+		_ => (seconds / 86_400, "day"),
+	};
+	format!("{value} {unit}{} ago", if value == 1 { "" } else { "s" })
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::{path::PathBuf, time::Duration};
+
+	#[test]
+	fn test_describe() {
+		let binary = CachedBinary {
+			name: "polkadot".into(),
+			version: Some("v1.7.0".into()),
+			path: PathBuf::from("/cache/polkadot-v1.7.0"),
+			size: 2 * 1024 * 1024,
+			source: Some("https://github.com/paritytech/polkadot-sdk".into()),
+			last_used: Some(SystemTime::now() - Duration::from_secs(7_200)),
+		};
+		assert_eq!(
+			describe(&binary),
+			"polkadot v1.7.0 (2.0 MiB), last used 2 hours ago\nhttps://github.com/paritytech/polkadot-sdk"
+		);
+	}
+
+	#[test]
+	fn test_elapsed() {
+		let ago = |seconds| SystemTime::now() - Duration::from_secs(seconds);
+		assert_eq!(elapsed(ago(5)), "just now");
+		assert_eq!(elapsed(ago(60)), "1 minute ago");
+		assert_eq!(elapsed(ago(7_200)), "2 hours ago");
+		assert_eq!(elapsed(ago(3 * 86_400)), "3 days ago");
+		// Times in the future are treated as now
+		assert_eq!(elapsed(SystemTime::now() + Duration::from_secs(60)), "just now");
+	}
+}
@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: GPL-3.0
+
+mod parachain;
+
+use clap::{Args, Subcommand};
+
+#[derive(Args)]
+#[command(args_conflicts_with_subcommands = true)]
+pub(crate) struct DownArgs {
+	#[command(subcommand)]
+	pub(crate) command: DownCommands,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum DownCommands {
+	/// Terminate a local network launched in detached mode.
+	#[clap(alias = "p")]
+	Parachain(parachain::DownParachainCommand),
+}
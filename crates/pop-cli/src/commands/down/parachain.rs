@@ -0,0 +1,80 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use crate::style::{style, Theme};
+use clap::Args;
+use cliclack::{clear_screen, intro, log, outro, outro_cancel, set_theme};
+use pop_parachains::{create_snapshot, Registry};
+use std::path::PathBuf;
+
+#[derive(Args)]
+pub(crate) struct DownParachainCommand {
+	/// The identifier of the network to terminate, as shown by `pop status parachain`. Can be
+	/// omitted when only a single network is running.
+	id: Option<String>,
+	/// Terminate all networks.
+	#[arg(short, long, action, conflicts_with = "id")]
+	all: bool,
+	/// Once terminated, save a snapshot of the state of the network (node databases, keystores
+	/// and chain specifications) to the specified path, as a gzipped tarball. The network can then
+	/// be launched again from this state via `pop up parachain --restore`.
+	#[arg(long, conflicts_with = "all")]
+	snapshot: Option<PathBuf>,
+}
+
+impl DownParachainCommand {
+	pub(crate) async fn execute(&self) -> anyhow::Result<()> {
+		clear_screen()?;
+		intro(format!("{}: Terminate a local network", style(" Pop CLI ").black().on_magenta()))?;
+		set_theme(Theme);
+
+		let registry = Registry::new(&crate::cache()?);
+		// Networks which are no longer running are removed without signalling any processes, as
+		// their identifiers may since have been reused
+		for network in registry.prune()? {
+			log::info(format!("Removed network {} as it is no longer running", network.id))?;
+		}
+		let mut networks = registry.networks()?;
+		if !self.all {
+			networks = match &self.id {
+				Some(id) => networks.into_iter().filter(|n| &n.id == id).collect(),
+				None if networks.len() > 1 => {
+					outro_cancel(format!(
+						"🚫 Multiple networks are running, please specify one of: {}",
+						networks.iter().map(|n| n.id.as_str()).collect::<Vec<_>>().join(", ")
+					))?;
+					return Ok(());
+				},
+				None => networks,
+			};
+		}
+		if networks.is_empty() {
+			outro_cancel(match &self.id {
+				Some(id) => format!("🚫 No network found with identifier {id}."),
+				None => "🚫 No networks are running.".into(),
+			})?;
+			return Ok(());
+		}
+
+		for network in networks {
+			let spinner = cliclack::spinner();
+			spinner.start(format!("Terminating network {}...", network.id));
+			if let Err(e) = network.terminate().await {
+				spinner.error(format!("🚫 Network {} could not be terminated: {e}", network.id));
+				continue;
+			}
+			registry.remove(&network.id)?;
+			spinner.stop(format!("✅ Network {} terminated.", network.id));
+			if let Some(path) = &self.snapshot {
+				let spinner = cliclack::spinner();
+				spinner.start(format!("📸 Saving snapshot of network {}...", network.id));
+				match create_snapshot(&network.base_dir, path) {
+					Ok(()) => spinner.stop(format!("✅ Snapshot saved to {}.", path.display())),
+					Err(e) => spinner.error(format!("🚫 The snapshot could not be saved: {e}")),
+				}
+			}
+			log::info(format!("Network data remains at {}", network.base_dir.display()))?;
+		}
+		outro("Done")?;
+		Ok(())
+	}
+}
@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: GPL-3.0
+
+pub(crate) mod parachain;
+
+use clap::{Args, Subcommand};
+
+#[derive(Args)]
+#[command(args_conflicts_with_subcommands = true)]
+pub(crate) struct LogsArgs {
+	#[command(subcommand)]
+	pub(crate) command: LogsCommands,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum LogsCommands {
+	/// Show the logs of the nodes of a local network launched in detached mode.
+	#[clap(alias = "p")]
+	Parachain(parachain::LogsParachainCommand),
+}
@@ -0,0 +1,133 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use clap::Args;
+use cliclack::{log, outro_cancel};
+use console::Style;
+use pop_parachains::{Level, LogFilter, NetworkLogs, Registry};
+use std::{collections::HashMap, time::Duration};
+use tokio::time::sleep;
+
+/// The interval at which logs are checked for new lines when following them.
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Args)]
+pub(crate) struct LogsParachainCommand {
+	/// The names of the nodes whose logs are shown, defaulting to all nodes.
+	nodes: Vec<String>,
+	/// The identifier of the network, as shown by `pop status parachain`. Can be omitted when
+	/// only a single network is running.
+	#[arg(short, long)]
+	network: Option<String>,
+	/// Keep showing new lines as they are logged, until interrupted.
+	#[arg(short, long, action)]
+	follow: bool,
+	#[command(flatten)]
+	filter: LogFilterArgs,
+}
+
+/// The options for filtering the logs of nodes.
+#[derive(Args)]
+pub(crate) struct LogFilterArgs {
+	/// Only show lines logged at the specified level or more severe.
+	#[arg(long, value_parser = Level::NAMES)]
+	level: Option<String>,
+	/// Only show lines matching the specified regular expression.
+	#[arg(long)]
+	grep: Option<String>,
+}
+
+impl LogFilterArgs {
+	// The filter for log lines, as specified.
+	pub(crate) fn filter(&self) -> anyhow::Result<LogFilter> {
+		let mut filter = LogFilter::new();
+		if let Some(level) = self.level.as_deref().and_then(Level::parse) {
+			filter = filter.with_level(level);
+		}
+		if let Some(pattern) = &self.grep {
+			filter = filter.with_pattern(pattern)?;
+		}
+		Ok(filter)
+	}
+}
+
+impl LogsParachainCommand {
+	pub(crate) async fn execute(&self) -> anyhow::Result<()> {
+		let registry = Registry::new(&crate::cache()?);
+		let networks = registry.networks()?;
+		let network = match &self.network {
+			Some(id) => networks.into_iter().find(|n| &n.id == id),
+			None if networks.len() > 1 => {
+				outro_cancel(format!(
+					"🚫 Multiple networks are running, please specify one of: {}",
+					networks.iter().map(|n| n.id.as_str()).collect::<Vec<_>>().join(", ")
+				))?;
+				return Ok(());
+			},
+			None => networks.into_iter().next(),
+		};
+		let Some(network) = network else {
+			outro_cancel(match &self.network {
+				Some(id) => format!("🚫 No network found with identifier {id}."),
+				None => "🚫 No networks are running.".into(),
+			})?;
+			return Ok(());
+		};
+		if self.follow && !network.is_running() {
+			log::warning(format!("⚠️ Network {} is no longer running.", network.id))?;
+		}
+		let mut logs = NetworkLogs::new(&network, &self.nodes, self.filter.filter()?)?;
+		match self.follow {
+			true => tokio::select! {
+				result = follow(&mut logs) => result,
+				_ = tokio::signal::ctrl_c() => Ok(()),
+			},
+			false => print(&mut logs),
+		}
+	}
+}
+
+/// Shows new lines logged by the nodes of a network as they are written, each prefixed by the
+/// name of the node.
+///
+/// # Arguments
+///
+/// * `logs` - the logs of the network
+pub(crate) async fn follow(logs: &mut NetworkLogs) -> anyhow::Result<()> {
+	let prefixes = prefixes(logs);
+	loop {
+		for line in logs.read()? {
+			println!("{} {}", prefixes[&line.node], line.line);
+		}
+		sleep(FOLLOW_INTERVAL).await;
+	}
+}
+
+// Shows the lines logged by the nodes of a network so far.
+fn print(logs: &mut NetworkLogs) -> anyhow::Result<()> {
+	let prefixes = prefixes(logs);
+	for line in logs.read()? {
+		println!("{} {}", prefixes[&line.node], line.line);
+	}
+	Ok(())
+}
+
+// The prefix of the lines of each node: its name, padded to a common width and colored
+// distinctly.
+fn prefixes(logs: &NetworkLogs) -> HashMap<String, String> {
+	const COLORS: [console::Color; 6] = [
+		console::Color::Cyan,
+		console::Color::Magenta,
+		console::Color::Yellow,
+		console::Color::Green,
+		console::Color::Blue,
+		console::Color::Red,
+	];
+	let width = logs.nodes().map(|n| n.len()).max().unwrap_or_default();
+	logs.nodes()
+		.zip(COLORS.iter().cycle())
+		.map(|(node, color)| {
+			let prefix = format!("{node:>width$} |");
+			(node.to_string(), Style::new().fg(*color).apply_to(prefix).to_string())
+		})
+		.collect()
+}
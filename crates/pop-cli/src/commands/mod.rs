@@ -1,7 +1,17 @@
 // SPDX-License-Identifier: GPL-3.0
 
 pub(crate) mod build;
+#[cfg(feature = "parachain")]
+pub(crate) mod cache;
 pub(crate) mod call;
+#[cfg(feature = "parachain")]
+pub(crate) mod down;
+#[cfg(feature = "parachain")]
+pub(crate) mod logs;
 pub(crate) mod new;
+#[cfg(feature = "parachain")]
+pub(crate) mod status;
 pub(crate) mod test;
 pub(crate) mod up;
+#[cfg(feature = "parachain")]
+pub(crate) mod upgrade;
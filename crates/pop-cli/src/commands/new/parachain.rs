@@ -12,7 +12,9 @@ use std::{
 };
 
 use cliclack::{clear_screen, confirm, input, intro, log, outro, outro_cancel, set_theme};
-use pop_parachains::{instantiate_template_dir, Config, Git, GitHub, Provider, Release, Template};
+use pop_parachains::{
+	instantiate_template_dir, Config, Git, GitHubClient, Provider, Release, Template,
+};
 use strum::VariantArray;
 
 #[derive(Args)]
@@ -73,7 +75,10 @@ impl NewParachainCommand {
 
 		return match &self.name {
 			// If user doesn't select the name guide them to generate a parachain.
-			None => guide_user_to_generate_parachain().await,
+			None => {
+				let github = GitHubClient::new().with_cache(&crate::cache()?);
+				guide_user_to_generate_parachain(&github).await
+			},
 			Some(name) => {
 				let provider = &self.provider.clone().unwrap_or_default();
 				let template = match &self.template {
@@ -95,7 +100,7 @@ impl NewParachainCommand {
 	}
 }
 
-async fn guide_user_to_generate_parachain() -> Result<()> {
+async fn guide_user_to_generate_parachain(github: &GitHubClient) -> Result<()> {
 	intro(format!("{}: Generate a parachain", style(" Pop CLI ").black().on_magenta()))?;
 
 	let mut prompt = cliclack::select("Select a template provider: ".to_string());
@@ -117,8 +122,7 @@ async fn guide_user_to_generate_parachain() -> Result<()> {
 	let provider = prompt.interact()?;
 	let template = display_select_options(provider)?;
 
-	let url = url::Url::parse(&template.repository_url()?).expect("valid repository url");
-	let latest_3_releases = GitHub::get_latest_n_releases(3, &url).await?;
+	let latest_3_releases = latest_releases(template, github).await?;
 
 	let mut release_name = None;
 	if latest_3_releases.len() > 0 {
@@ -150,6 +154,13 @@ async fn guide_user_to_generate_parachain() -> Result<()> {
 	)
 }
 
+// Looks up the latest three releases of the repository of a template, from which a release can be
+// selected.
+async fn latest_releases(template: &Template, github: &GitHubClient) -> Result<Vec<Release>> {
+	let url = url::Url::parse(&template.repository_url()?).expect("valid repository url");
+	github.get_latest_n_releases(3, &url).await
+}
+
 fn generate_parachain_from_template(
 	name_template: &String,
 	provider: &Provider,
@@ -298,6 +309,7 @@ mod tests {
 	};
 	use clap::Parser;
 	use git2::Repository;
+	use pop_parachains::testing::serve;
 	use tempfile::tempdir;
 
 	#[tokio::test]
@@ -345,6 +357,31 @@ mod tests {
 		Ok(())
 	}
 
+	#[tokio::test]
+	async fn test_latest_releases() -> Result<()> {
+		let url = serve(|path, _| match path {
+			"/repos/r0gue-io/base-parachain/releases?per_page=100" =>
+				(200, include_bytes!("../../../../../tests/github/releases.json").to_vec()),
+			path => match path.strip_prefix("/repos/r0gue-io/base-parachain/git/ref/tags/") {
+				Some(tag) => (200, format!(r#"{{"object":{{"sha":"{tag}-sha"}}}}"#).into_bytes()),
+				None => (404, Vec::new()),
+			},
+		});
+		let github = GitHubClient::new().with_token(None).with_api(&url);
+		let releases = latest_releases(&Template::Base, &github).await?;
+		// Pre-releases are excluded
+		let releases: Vec<_> =
+			releases.iter().map(|r| (r.tag_name.as_str(), r.commit.as_deref())).collect();
+		assert_eq!(
+			releases,
+			[
+				("polkadot-v1.10.0", Some("polkadot-v1.10.0-sha")),
+				("polkadot-v1.9.0", Some("polkadot-v1.9.0-sha"))
+			]
+		);
+		Ok(())
+	}
+
 	#[test]
 	fn test_is_template_supported() -> Result<()> {
 		is_template_supported(&Provider::Pop, &Template::Base)?;
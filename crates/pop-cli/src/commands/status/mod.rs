@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: GPL-3.0
+
+mod parachain;
+
+use clap::{Args, Subcommand};
+
+#[derive(Args)]
+#[command(args_conflicts_with_subcommands = true)]
+pub(crate) struct StatusArgs {
+	#[command(subcommand)]
+	pub(crate) command: StatusCommands,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum StatusCommands {
+	/// List local networks launched in detached mode.
+	#[clap(alias = "p")]
+	Parachain(parachain::StatusParachainCommand),
+}
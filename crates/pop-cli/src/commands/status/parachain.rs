@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use crate::style::{style, Theme};
+use clap::Args;
+use cliclack::{clear_screen, intro, log, outro, set_theme};
+use console::{Emoji, Style};
+use pop_parachains::Registry;
+
+#[derive(Args)]
+pub(crate) struct StatusParachainCommand {
+	/// Remove networks which are no longer running from the registry.
+	#[arg(short, long, action)]
+	prune: bool,
+}
+
+impl StatusParachainCommand {
+	pub(crate) async fn execute(&self) -> anyhow::Result<()> {
+		clear_screen()?;
+		intro(format!("{}: Local networks", style(" Pop CLI ").black().on_magenta()))?;
+		set_theme(Theme);
+
+		let registry = Registry::new(&crate::cache()?);
+		let networks = registry.networks()?;
+		if networks.is_empty() {
+			outro("No networks are running.")?;
+			return Ok(());
+		}
+
+		let bar = Style::new().magenta().dim().apply_to(Emoji("│", "|"));
+		for network in networks {
+			let running = network.is_running();
+			if !running && self.prune {
+				registry.remove(&network.id)?;
+				log::info(format!("Removed network {} as it is no longer running", network.id))?;
+				continue;
+			}
+			let mut result = format!(
+				"{} {} (pid: {}, base dir: {})",
+				if running { "🟢" } else { "🔴 [stopped]" },
+				network.id,
+				network.pid,
+				network.base_dir.display()
+			);
+			for chain in std::iter::once(&network.relay_chain).chain(network.parachains.iter()) {
+				result.push_str(&format!("\n{bar}  ⛓️ {}", chain.label()));
+				for node in &chain.nodes {
+					result.push_str(&format!("\n{bar}       {}: {}", node.name, node.ws_uri));
+				}
+			}
+			match running {
+				true => log::success(result)?,
+				false => log::warning(result)?,
+			}
+		}
+		outro("Use `pop down parachain <id>` to terminate a network.")?;
+		Ok(())
+	}
+}
@@ -3,7 +3,7 @@
 #[cfg(feature = "contract")]
 mod contract;
 #[cfg(feature = "parachain")]
-mod parachain;
+pub(crate) mod parachain;
 
 use clap::{Args, Subcommand};
 
@@ -1,50 +1,191 @@
 // SPDX-License-Identifier: GPL-3.0
 
-use crate::style::{style, Theme};
-use clap::Args;
+use crate::{
+	commands::logs::parachain::{follow, LogFilterArgs},
+	style::{style, ProgressReporter, Theme},
+};
+use clap::{Args, ValueEnum};
 use cliclack::{
 	clear_screen, confirm, intro, log, multi_progress, outro, outro_cancel, set_theme, ProgressBar,
 };
 use console::{Emoji, Style};
-use pop_parachains::{NetworkNode, Status, Zombienet};
-use std::time::Duration;
+use pop_parachains::{
+	has_errors, validate, wait_for_blocks, NetworkConfigBuilder, NetworkInfo, NetworkLogs,
+	Registry, Severity, SourceOptions, Zombienet,
+};
+use std::{
+	env::{args_os, current_exe},
+	fs::{create_dir_all, read_to_string, remove_file},
+	path::{Path, PathBuf},
+	process::{Command, Stdio},
+	time::Duration,
+};
+use tempfile::NamedTempFile;
 use tokio::time::sleep;
 
+#[cfg(unix)]
+use std::os::unix::process::CommandExt;
+
+/// The maximum time to wait for parachains to produce blocks, when requested.
+const READY_TIMEOUT: Duration = Duration::from_secs(600);
+
 #[derive(Args)]
 pub(crate) struct ZombienetCommand {
-	/// The Zombienet configuration file to be used.
+	/// The Zombienet configuration file to be used, as TOML, JSON or YAML. When not specified, a
+	/// network configuration is generated from the provided relay chain validators, parachains
+	/// and system parachains (see `--with-system-parachain`).
 	#[arg(short, long)]
-	file: String,
+	file: Option<String>,
 	/// The version of Polkadot to be used for the relay chain, as per the release tag (e.g.
-	/// "v1.7.0").
+	/// "v1.7.0"), a version requirement (e.g. ">=1.9, <1.11"), "latest" or "latest-rc" (the latest
+	/// release, including release candidates). Defaults to the latest release.
 	#[arg(short, long)]
 	relay_chain: Option<String>,
 	/// The version of Polkadot to be used for a system parachain, as per the release tag (e.g.
 	/// "v1.7.0").
 	#[arg(short, long)]
 	system_parachain: Option<String>,
-	/// The url of the git repository of a parachain to be used, with branch/release tag specified as #fragment (e.g. 'https://github.com/org/repository#tag'). A specific binary name can also be optionally specified via query string parameter (e.g. 'https://github.com/org/repository?binaryname#tag'), defaulting to the name of the repository when not specified.
+	/// The url of the git repository of a parachain to be used, with branch/release tag/commit specified as #fragment (e.g. 'https://github.com/org/repository#tag'). Branches and tags are resolved to the commit they currently refer to, with the resulting binary cached per commit. Repositories can be hosted on GitHub, GitLab, Gitea or the local filesystem (e.g. 'file:///srv/git/repository.git'), with a 'gitlab+' or 'gitea+' scheme prefix identifying self-hosted instances (e.g. 'gitlab+https://git.example.com/org/repository'). A specific binary name can also be optionally specified via query string parameter (e.g. 'https://github.com/org/repository?binaryname#tag'), defaulting to the name of the repository when not specified. Alternatively, the path to a local binary (e.g. './target/release/node'), a binary within a local OCI/Docker image archive (e.g. 'docker-archive:./image.tar?/usr/bin/node#tag') or a url template with {version} and {arch} placeholders (e.g. 'https://example.com/{version}/node-{arch}?node#tag') can be specified. The path to a runtime WASM (e.g. './runtime.compact.compressed.wasm') can also be specified for parachains without a node, which are launched using 'polkadot-omni-node' with a chain spec generated by 'chain-spec-builder'. Each can optionally be prefixed with the parachain identifier (e.g. '2000=./target/release/node').
 	#[arg(short, long)]
 	parachain: Option<Vec<String>>,
+	/// The name of a system parachain to be included when generating a network configuration
+	/// (e.g. "asset-hub", "bridge-hub", "coretime" or "people").
+	#[arg(long, conflicts_with = "file")]
+	with_system_parachain: Option<Vec<String>>,
+	/// The number of relay chain validators, when generating a network configuration. Defaults to
+	/// two, or one per parachain when launching more parachains.
+	#[arg(long, conflicts_with = "file")]
+	relay_validators: Option<u8>,
+	/// Write the generated network configuration to the specified path, as TOML, so that it can be
+	/// reused later via `--file`.
+	#[arg(long, conflicts_with = "file")]
+	emit_config: Option<PathBuf>,
 	/// Whether the output should be verbose.
 	#[arg(short, long, action)]
 	verbose: bool,
+	/// The format in which the connection information of the launched network is output. JSON
+	/// output includes the names, websocket and RPC uris, Prometheus ports, log paths and commands
+	/// of all nodes.
+	#[arg(short, long, value_enum, default_value_t = Output::Human)]
+	output: Output,
+	/// Write the connection information of the launched network to the specified path, as JSON.
+	#[arg(long)]
+	write_info: Option<PathBuf>,
+	/// Wait until every parachain has finalized the specified number of blocks before reporting
+	/// the network as launched.
+	#[arg(long)]
+	wait_for_blocks: Option<u64>,
+	/// Show the logs of all nodes once the network is launched, each line prefixed by the name of
+	/// its node, until interrupted.
+	#[arg(long, action, conflicts_with = "detach")]
+	follow: bool,
+	#[command(flatten)]
+	log_filter: LogFilterArgs,
+	/// Restore the state of the network from a snapshot, as created via `pop down parachain
+	/// --snapshot`, so that its nodes resume from the saved state rather than from genesis. The
+	/// network configuration must match that of the network from which the snapshot was created.
+	#[arg(long)]
+	restore: Option<PathBuf>,
+	/// Launch a parachain from the state of a live chain, downloaded from one of its nodes (e.g.
+	/// 'wss://rpc.example.com'), optionally at a specific block number or hash (e.g.
+	/// 'wss://rpc.example.com@1000'). Can be prefixed with the parachain identifier (e.g.
+	/// '2000=wss://rpc.example.com'), which can be omitted when the network includes a single
+	/// parachain. The state of the pallets required for local collators to author blocks is
+	/// retained from the local chain specification.
+	#[arg(long, conflicts_with = "restore")]
+	fork: Option<Vec<String>>,
+	/// Launch the network in the background, returning once it is running. Use `pop status
+	/// parachain` to list running networks and `pop down parachain` to terminate them.
+	#[arg(short, long, action)]
+	detach: bool,
+	/// Resolve binaries purely from the cache, without accessing the network. When no relay chain
+	/// version is specified, the newest cached version is used.
+	#[arg(long, action, env = "POP_OFFLINE")]
+	offline: bool,
+	/// Keep the source checkout and build directory of binaries built from git repositories within
+	/// the cache, so that subsequent builds are incremental.
+	#[arg(long, action)]
+	incremental: bool,
+	/// Only validate the network configuration file, reporting any issues without launching the
+	/// network.
+	#[arg(long, action)]
+	check: bool,
+	/// Supervise the network as a background process, as launched via `--detach`.
+	#[arg(long, action, hide = true)]
+	supervise: bool,
+}
+
+/// The format of the connection information of a launched network.
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+enum Output {
+	/// A summary suitable for display.
+	Human,
+	/// JSON, for consumption by other tools.
+	Json,
 }
+
 impl ZombienetCommand {
 	pub(crate) async fn execute(&self) -> anyhow::Result<()> {
+		if self.supervise {
+			return self.supervise().await;
+		}
 		clear_screen()?;
 		intro(format!("{}: Deploy a parachain", style(" Pop CLI ").black().on_magenta()))?;
 		set_theme(Theme);
+		let (file, _generated) = self.network_config()?;
+		if let Some(path) = &self.emit_config {
+			log::info(format!("ℹ️ The network configuration was written to {}", path.display()))?;
+		}
+		// Validate the network configuration before resolving any binaries
+		let diagnostics = validate(&file)?;
+		for diagnostic in &diagnostics {
+			match diagnostic.severity {
+				Severity::Error => log::error(diagnostic)?,
+				Severity::Warning => log::warning(diagnostic)?,
+			}
+		}
+		if self.check {
+			if has_errors(&diagnostics) {
+				outro_cancel("🚫 The network configuration is invalid.")?;
+				anyhow::bail!("invalid network configuration: {}", file.display());
+			}
+			outro("✅ The network configuration is valid.")?;
+			return Ok(());
+		}
+		if has_errors(&diagnostics) {
+			outro_cancel(
+				"🚫 Cannot deploy parachain to local network until the network configuration is valid.",
+			)?;
+			return Ok(());
+		}
 		// Parse arguments
 		let cache = crate::cache()?;
-		let mut zombienet = Zombienet::new(
+		let mut zombienet = match Zombienet::new(
 			cache.clone(),
-			&self.file,
+			&file.to_string_lossy(),
 			self.relay_chain.as_ref(),
 			self.system_parachain.as_ref(),
 			self.parachain.as_ref(),
+			self.offline,
 		)
-		.await?;
+		.await
+		{
+			Ok(zombienet) => zombienet,
+			Err(e) if self.offline => {
+				outro_cancel(format!(
+					"🚫 Cannot deploy parachain to local network while offline: {e}"
+				))?;
+				return Ok(());
+			},
+			Err(e) => return Err(e.into()),
+		};
+		for warning in zombienet.warnings() {
+			log::warning(format!("⚠️ {warning}"))?;
+		}
+		log::info(format!(
+			"ℹ️ Using polkadot {} for the relay chain",
+			zombienet.relay_chain_version()
+		))?;
 		// Check if any binaries need to be sourced
 		let missing = zombienet.missing_binaries();
 		if missing.len() > 0 {
@@ -61,84 +202,81 @@ impl ZombienetCommand {
 			}
 			log::info(format!("ℹ️ They will be cached at {}", &cache.to_str().unwrap()))?;
 			// Source binaries
+			let options = SourceOptions { incremental: self.incremental, ..Default::default() };
 			for binary in missing {
 				let multi = multi_progress(format!("📦 Sourcing {}...", binary.name));
 				let progress = multi.add(cliclack::spinner());
 				let progress_reporter = ProgressReporter(&progress);
-				for attempt in (0..=1).rev() {
-					if let Err(e) = binary.source(&cache, progress_reporter).await {
-						match attempt {
-							0 => {
-								progress.error(format!("🚫 Sourcing failed: {e}"));
-								multi.stop();
-								return Ok(());
-							},
-							_ => {
-								progress.error("🚫 Sourcing attempt failed, retrying...");
-								sleep(Duration::from_secs(1)).await;
-							},
-						}
-					}
+				if let Err(e) = binary.source_with(&cache, progress_reporter, &options).await {
+					progress.error(format!("🚫 Sourcing failed: {e}"));
+					multi.stop();
+					return Ok(());
 				}
 				progress.stop(format!("✅ Sourcing {} complete.", binary.name));
 				multi.stop();
 			}
 		}
+		if self.detach {
+			return self.detach(&cache).await;
+		}
+		if let Some(snapshot) = &self.restore {
+			match zombienet.restore(snapshot) {
+				Ok(base_dir) => log::info(format!(
+					"ℹ️ Restored snapshot {} to {}",
+					snapshot.display(),
+					base_dir.display()
+				))?,
+				Err(e) => {
+					outro_cancel(format!("🚫 Cannot restore snapshot: {e}"))?;
+					return Ok(());
+				},
+			}
+		}
+		for fork in self.fork.iter().flatten() {
+			let spinner = cliclack::spinner();
+			spinner.start(format!("🍴 Forking {fork}..."));
+			match zombienet.fork(fork, ProgressReporter(&spinner)).await {
+				Ok(id) => spinner.stop(format!("✅ Parachain {id} forked from {fork}.")),
+				Err(e) => {
+					spinner.error(format!("🚫 Forking failed: {e}"));
+					outro_cancel("🚫 Cannot deploy parachain to local network.")?;
+					return Ok(());
+				},
+			}
+		}
 		// Finally spawn network and wait for signal to terminate
 		let spinner = cliclack::spinner();
 		spinner.start("🚀 Launching local network...");
 		//tracing_subscriber::fmt().init();
 		match zombienet.spawn().await {
 			Ok(network) => {
-				let mut result =
-					"🚀 Network launched successfully - ctrl-c to terminate".to_string();
-				let base_dir = network.base_dir().expect("base_dir expected to exist");
-				let bar = Style::new().magenta().dim().apply_to(Emoji("│", "|"));
-
-				let output = |node: &NetworkNode| -> String {
-					let name = node.name();
-					let mut output = format!(
-						"\n{bar}       {name}:
-{bar}         portal: https://polkadot.js.org/apps/?rpc={}#/explorer
-{bar}         logs: tail -f {base_dir}/{name}/{name}.log",
-						node.ws_uri(),
-					);
-					if self.verbose {
-						output += &format!(
-							"\n{bar}         command: {} {}",
-							node.spec().command(),
-							node.args().join(" ")
-						);
-					}
-					output
-				};
-				// Add relay info
-				let mut validators = network.relaychain().nodes();
-				validators.sort_by_key(|n| n.name());
-				result.push_str(&format!("\n{bar}  ⛓️ {}", network.relaychain().chain()));
-				for node in validators {
-					result.push_str(&output(node));
+				let info = NetworkInfo::new(&network, std::process::id())?;
+				if let Err(e) = self.wait_until_ready(&info, &spinner).await {
+					spinner.error(format!("🚫 {e}"));
+					outro_cancel("🚫 The network did not become ready.")?;
+					anyhow::bail!("the network did not become ready: {e}");
 				}
-				// Add parachain info
-				let mut parachains = network.parachains();
-				parachains.sort_by_key(|p| p.para_id());
-				for parachain in network.parachains() {
-					result.push_str(&format!(
-						"\n{bar}  ⛓️ {}",
-						parachain.chain_id().map_or(
-							format!("para_id: {}", parachain.para_id()),
-							|chain| format!("{chain}: {}", parachain.para_id())
-						)
-					));
-					let mut collators = parachain.collators();
-					collators.sort_by_key(|n| n.name());
-					for node in collators {
-						result.push_str(&output(node));
-					}
+				self.write_info(&info)?;
+				match self.output {
+					Output::Human => spinner.stop(format!(
+						"🚀 Network launched successfully - ctrl-c to terminate{}",
+						summary(&info, self.verbose)
+					)),
+					Output::Json => {
+						spinner.stop("🚀 Network launched successfully - ctrl-c to terminate");
+						println!("{}", info.to_json()?);
+					},
+				}
+				match self.follow {
+					true => {
+						let mut logs = NetworkLogs::new(&info, &[], self.log_filter.filter()?)?;
+						tokio::select! {
+							result = follow(&mut logs) => result?,
+							result = tokio::signal::ctrl_c() => result?,
+						}
+					},
+					false => tokio::signal::ctrl_c().await?,
 				}
-
-				spinner.stop(result);
-				tokio::signal::ctrl_c().await?;
 				outro("Done")?;
 			},
 			Err(e) => {
@@ -148,14 +286,214 @@ impl ZombienetCommand {
 
 		Ok(())
 	}
+
+	// Launches the network within a background process, waiting until it has been registered.
+	async fn detach(&self, cache: &Path) -> anyhow::Result<()> {
+		let spinner = cliclack::spinner();
+		spinner.start("🚀 Launching local network in the background...");
+		let registry = Registry::new(cache);
+		create_dir_all(registry.path())?;
+		let log = tempfile::Builder::new()
+			.prefix("network-")
+			.suffix(".log")
+			.tempfile_in(registry.path())?
+			.keep()?
+			.1;
+		// Relaunch the current command as a supervising process, in its own process group so that
+		// it is unaffected by signals sent to this terminal
+		let mut command = Command::new(current_exe()?);
+		command
+			.args(args_os().skip(1))
+			.arg("--supervise")
+			.stdin(Stdio::null())
+			.stdout(Stdio::from(std::fs::File::create(&log)?))
+			.stderr(Stdio::from(std::fs::File::options().append(true).open(&log)?));
+		#[cfg(unix)]
+		command.process_group(0);
+		let mut child = command.spawn()?;
+		let pid = child.id();
+		// Wait for the network to be registered by the supervising process
+		let network = loop {
+			if let Some(network) =
+				registry.networks()?.into_iter().find(|n| n.pid == pid && n.is_running())
+			{
+				break network;
+			}
+			if let Some(status) = child.try_wait()? {
+				spinner.error(format!("🚫 The network could not be launched ({status})."));
+				outro_cancel(format!(
+					"{}\nSee {} for more details.",
+					read_to_string(&log).unwrap_or_default().trim(),
+					log.display()
+				))?;
+				return Ok(());
+			}
+			sleep(Duration::from_millis(500)).await;
+		};
+		// The log is only of use should the launch fail, so is removed on a best-effort basis
+		let _ = remove_file(&log);
+
+		if let Err(e) = self.wait_until_ready(&network, &spinner).await {
+			spinner.error(format!("🚫 {e}"));
+			outro_cancel(format!(
+				"Use `pop down parachain {}` to terminate the network.",
+				network.id
+			))?;
+			anyhow::bail!("network {} did not become ready: {e}", network.id);
+		}
+		self.write_info(&network)?;
+		let result = format!("🚀 Network {} launched successfully in the background", network.id);
+		match self.output {
+			Output::Human => spinner.stop(format!("{result}{}", summary(&network, self.verbose))),
+			Output::Json => {
+				spinner.stop(result);
+				println!("{}", network.to_json()?);
+			},
+		}
+		outro(format!("Use `pop down parachain {}` to terminate the network.", network.id))?;
+		Ok(())
+	}
+
+	// Spawns the network and registers it, keeping it running until signalled to terminate.
+	async fn supervise(&self) -> anyhow::Result<()> {
+		let cache = crate::cache()?;
+		let (file, _generated) = self.network_config()?;
+		let mut zombienet = Zombienet::new(
+			cache.clone(),
+			&file.to_string_lossy(),
+			self.relay_chain.as_ref(),
+			self.system_parachain.as_ref(),
+			self.parachain.as_ref(),
+			self.offline,
+		)
+		.await?;
+		for warning in zombienet.warnings() {
+			eprintln!("Warning: {warning}");
+		}
+		println!("Using polkadot {} for the relay chain", zombienet.relay_chain_version());
+		let missing = zombienet.missing_binaries();
+		if !missing.is_empty() {
+			anyhow::bail!(
+				"the following missing binaries are required: {}",
+				missing.iter().map(|b| b.name.as_str()).collect::<Vec<_>>().join(", ")
+			);
+		}
+		if let Some(snapshot) = &self.restore {
+			let base_dir = zombienet.restore(snapshot)?;
+			println!("Restored snapshot {} to {}", snapshot.display(), base_dir.display());
+		}
+		for fork in self.fork.iter().flatten() {
+			let id = zombienet.fork(fork, ()).await?;
+			println!("Parachain {id} forked from {fork}");
+		}
+		let network = zombienet.spawn().await?;
+		let info = NetworkInfo::new(&network, std::process::id())?;
+		let registry = Registry::new(&cache);
+		registry.register(&info)?;
+		println!("Network {} launched successfully", info.id);
+		terminated().await?;
+		registry.remove(&info.id)?;
+		println!("Network {} terminated", info.id);
+		Ok(())
+	}
+
+	// Waits until every parachain of a launched network has finalized the requested number of
+	// blocks, if any.
+	async fn wait_until_ready(
+		&self,
+		network: &NetworkInfo,
+		spinner: &ProgressBar,
+	) -> anyhow::Result<()> {
+		let Some(blocks) = self.wait_for_blocks else {
+			return Ok(());
+		};
+		spinner.start(format!("⏳ Waiting for parachains to finalize {blocks} blocks..."));
+		wait_for_blocks(network, blocks, READY_TIMEOUT, ProgressReporter(spinner)).await?;
+		Ok(())
+	}
+
+	// Writes the connection information of a launched network, if requested.
+	fn write_info(&self, network: &NetworkInfo) -> anyhow::Result<()> {
+		if let Some(path) = &self.write_info {
+			std::fs::write(path, network.to_json()?)?;
+			log::info(format!("ℹ️ The network information was written to {}", path.display()))?;
+		}
+		Ok(())
+	}
+
+	// Resolves the network configuration file, generating one from the provided arguments when no
+	// file is specified. Any temporary file is also returned, so that it is retained while in use.
+	fn network_config(&self) -> anyhow::Result<(PathBuf, Option<NamedTempFile>)> {
+		if let Some(file) = &self.file {
+			return Ok((PathBuf::from(file), None));
+		}
+		let mut builder = NetworkConfigBuilder::new();
+		if let Some(validators) = self.relay_validators {
+			builder = builder.validators(validators);
+		}
+		for system_parachain in self.with_system_parachain.iter().flatten() {
+			builder = builder.system_parachain(system_parachain)?;
+		}
+		for parachain in self.parachain.iter().flatten() {
+			builder = builder.parachain(parachain)?;
+		}
+		match &self.emit_config {
+			Some(path) => {
+				builder.write(path)?;
+				Ok((path.clone(), None))
+			},
+			None => {
+				let file =
+					tempfile::Builder::new().prefix("network-").suffix(".toml").tempfile()?;
+				builder.write(file.path())?;
+				Ok((file.path().to_path_buf(), Some(file)))
+			},
+		}
+	}
 }
 
-/// Reports any observed status updates to a progress bar.
-#[derive(Copy, Clone)]
-struct ProgressReporter<'a>(&'a ProgressBar);
+// A summary of the chains of a launched network and how to connect to their nodes, suitable for
+// display.
+fn summary(network: &NetworkInfo, verbose: bool) -> String {
+	let bar = Style::new().magenta().dim().apply_to(Emoji("│", "|"));
+	let mut summary = String::new();
+	for chain in std::iter::once(&network.relay_chain).chain(network.parachains.iter()) {
+		summary.push_str(&format!("\n{bar}  ⛓️ {}", chain.label()));
+		for node in &chain.nodes {
+			summary.push_str(&format!(
+				"\n{bar}       {}:
+{bar}         portal: https://polkadot.js.org/apps/?rpc={}#/explorer
+{bar}         logs: tail -f {}",
+				node.name,
+				node.ws_uri,
+				node.log_path.display()
+			));
+			if verbose {
+				summary.push_str(&format!(
+					"\n{bar}         command: {} {}",
+					node.command,
+					node.args.join(" ")
+				));
+			}
+		}
+	}
+	summary
+}
 
-impl Status for ProgressReporter<'_> {
-	fn update(&self, status: &str) {
-		self.0.start(status.replace("   Compiling", "Compiling"))
+// Waits until the process is signalled to terminate, whether via ctrl-c (SIGINT) or otherwise
+// (SIGTERM, e.g. by `kill` or a process manager), so that the network is shut down and its
+// registry entry removed rather than its nodes being orphaned.
+async fn terminated() -> anyhow::Result<()> {
+	#[cfg(unix)]
+	{
+		use tokio::signal::unix::{signal, SignalKind};
+		let mut terminate = signal(SignalKind::terminate())?;
+		tokio::select! {
+			result = tokio::signal::ctrl_c() => result?,
+			_ = terminate.recv() => {},
+		}
 	}
+	#[cfg(not(unix))]
+	tokio::signal::ctrl_c().await?;
+	Ok(())
 }
@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: GPL-3.0
+
+mod parachain;
+
+use clap::{Args, Subcommand};
+
+#[derive(Args)]
+#[command(args_conflicts_with_subcommands = true)]
+pub(crate) struct UpgradeArgs {
+	#[command(subcommand)]
+	pub(crate) command: UpgradeCommands,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum UpgradeCommands {
+	/// Upgrade the runtime of a running parachain, such as one of a local network.
+	#[clap(alias = "p")]
+	Parachain(parachain::UpgradeParachainCommand),
+}
@@ -0,0 +1,52 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use crate::style::{style, ProgressReporter, Theme};
+use clap::Args;
+use cliclack::{clear_screen, intro, outro, outro_cancel, set_theme};
+use pop_parachains::upgrade_runtime;
+use std::path::PathBuf;
+
+#[derive(Args)]
+pub(crate) struct UpgradeParachainCommand {
+	/// The runtime to upgrade to, as built via `pop build parachain` (e.g.
+	/// './target/release/wbuild/runtime/runtime.compact.compressed.wasm').
+	#[arg(short, long)]
+	wasm: PathBuf,
+	/// Websocket endpoint of a node of the parachain.
+	#[arg(long, value_parser, default_value = "ws://localhost:9944")]
+	url: url::Url,
+	/// Secret key URI of the sudo account, used to submit the upgrade.
+	///
+	/// e.g.
+	/// - for a dev account "//Alice"
+	/// - with a password "//Alice///SECRET_PASSWORD"
+	#[arg(short, long, default_value = "//Alice")]
+	suri: String,
+}
+
+impl UpgradeParachainCommand {
+	pub(crate) async fn execute(&self) -> anyhow::Result<()> {
+		clear_screen()?;
+		intro(format!("{}: Upgrade a parachain runtime", style(" Pop CLI ").black().on_magenta()))?;
+		set_theme(Theme);
+
+		let spinner = cliclack::spinner();
+		spinner.start(format!("⬆️ Upgrading the runtime of {}...", self.url));
+		match upgrade_runtime(self.url.as_str(), &self.wasm, &self.suri, ProgressReporter(&spinner))
+			.await
+		{
+			Ok(upgrade) => {
+				spinner.stop(format!(
+					"✅ Runtime upgraded from spec version {} to {}.",
+					upgrade.previous_spec_version, upgrade.spec_version
+				));
+				outro("Done")?;
+			},
+			Err(e) => {
+				spinner.error(format!("🚫 {e}"));
+				outro_cancel("🚫 The runtime could not be upgraded.")?;
+			},
+		}
+		Ok(())
+	}
+}
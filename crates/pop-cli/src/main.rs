@@ -44,6 +44,24 @@ enum Commands {
 	#[clap(alias = "t")]
 	#[cfg(feature = "contract")]
 	Test(commands::test::TestArgs),
+	/// Terminate a local network launched in detached mode.
+	#[clap(alias = "d")]
+	#[cfg(feature = "parachain")]
+	Down(commands::down::DownArgs),
+	/// List local networks launched in detached mode.
+	#[clap(alias = "s")]
+	#[cfg(feature = "parachain")]
+	Status(commands::status::StatusArgs),
+	/// Show the logs of a local network launched in detached mode.
+	#[clap(alias = "l")]
+	#[cfg(feature = "parachain")]
+	Logs(commands::logs::LogsArgs),
+	/// Manage the binaries cached when launching local networks.
+	#[cfg(feature = "parachain")]
+	Cache(commands::cache::CacheArgs),
+	/// Upgrade the runtime of a running chain.
+	#[cfg(feature = "parachain")]
+	Upgrade(commands::upgrade::UpgradeArgs),
 }
 
 #[tokio::main]
@@ -81,6 +99,24 @@ async fn main() -> Result<()> {
 		Commands::Test(args) => match &args.command {
 			commands::test::TestCommands::Contract(cmd) => cmd.execute(),
 		},
+		#[cfg(feature = "parachain")]
+		Commands::Down(args) => match &args.command {
+			commands::down::DownCommands::Parachain(cmd) => cmd.execute().await,
+		},
+		#[cfg(feature = "parachain")]
+		Commands::Status(args) => match &args.command {
+			commands::status::StatusCommands::Parachain(cmd) => cmd.execute().await,
+		},
+		#[cfg(feature = "parachain")]
+		Commands::Logs(args) => match &args.command {
+			commands::logs::LogsCommands::Parachain(cmd) => cmd.execute().await,
+		},
+		#[cfg(feature = "parachain")]
+		Commands::Cache(args) => args.execute(),
+		#[cfg(feature = "parachain")]
+		Commands::Upgrade(args) => match &args.command {
+			commands::upgrade::UpgradeCommands::Parachain(cmd) => cmd.execute().await,
+		},
 	}
 }
 
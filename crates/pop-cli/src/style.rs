@@ -1,8 +1,12 @@
 // SPDX-License-Identifier: GPL-3.0
+#[cfg(feature = "parachain")]
+use cliclack::ProgressBar;
 use cliclack::ThemeState;
 #[cfg(any(feature = "parachain", feature = "contract"))]
 pub(crate) use console::style;
 use console::Style;
+#[cfg(feature = "parachain")]
+use pop_parachains::{DownloadProgress, Status};
 
 pub(crate) fn get_styles() -> clap::builder::Styles {
 	use clap::builder::styling::{AnsiColor, Color, Style};
@@ -40,3 +44,88 @@ impl cliclack::Theme for Theme {
 		"⚙".into()
 	}
 }
+
+/// Formats a number of bytes using binary units.
+#[cfg(feature = "parachain")]
+pub(crate) fn format_bytes(bytes: u64) -> String {
+	const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+	if bytes < 1024 {
+		return format!("{bytes} B");
+	}
+	let mut value = bytes as f64 / 1024.0;
+	let mut unit = 0;
+	while value >= 1024.0 && unit < UNITS.len() - 1 {
+		value /= 1024.0;
+		unit += 1;
+	}
+	format!("{value:.1} {}", UNITS[unit])
+}
+
+/// Reports any observed status updates to a progress bar.
+#[cfg(feature = "parachain")]
+#[derive(Copy, Clone)]
+pub(crate) struct ProgressReporter<'a>(pub(crate) &'a ProgressBar);
+
+#[cfg(feature = "parachain")]
+impl Status for ProgressReporter<'_> {
+	fn update(&self, status: &str) {
+		self.0.start(status.replace("   Compiling", "Compiling"))
+	}
+
+	fn progress(&self, progress: &DownloadProgress) {
+		let name = progress.url.rsplit('/').next().unwrap_or(progress.url);
+		self.0.start(format!("Downloading {name}: {}", render_progress(progress)))
+	}
+}
+
+#[cfg(feature = "parachain")]
+// Renders the progress of a download as a bar, along with the bytes downloaded and throughput.
+fn render_progress(progress: &DownloadProgress) -> String {
+	const WIDTH: u64 = 20;
+	let downloaded = format_bytes(progress.downloaded);
+	let throughput = format_bytes(progress.throughput());
+	match progress.total.filter(|t| *t > 0) {
+		Some(total) => {
+			let filled = (progress.downloaded.min(total) * WIDTH / total) as usize;
+			format!(
+				"[{}{}] {downloaded} / {} ({throughput}/s)",
+				"█".repeat(filled),
+				"░".repeat(WIDTH as usize - filled),
+				format_bytes(total)
+			)
+		},
+		None => format!("{downloaded} ({throughput}/s)"),
+	}
+}
+
+#[cfg(all(test, feature = "parachain"))]
+mod tests {
+	use super::*;
+	use std::time::Duration;
+
+	#[test]
+	fn test_format_bytes() {
+		assert_eq!(format_bytes(0), "0 B");
+		assert_eq!(format_bytes(1023), "1023 B");
+		assert_eq!(format_bytes(1024), "1.0 KiB");
+		assert_eq!(format_bytes(1_572_864), "1.5 MiB");
+		assert_eq!(format_bytes(3 * 1024 * 1024 * 1024), "3.0 GiB");
+	}
+
+	#[test]
+	fn test_render_progress() {
+		let mut progress = DownloadProgress {
+			url: "https://github.com/paritytech/polkadot-sdk/releases/download/polkadot-v1.7.0/polkadot",
+			downloaded: 5 * 1024 * 1024,
+			resumed: 0,
+			total: Some(20 * 1024 * 1024),
+			elapsed: Duration::from_secs(2),
+		};
+		assert_eq!(
+			render_progress(&progress),
+			"[█████░░░░░░░░░░░░░░░] 5.0 MiB / 20.0 MiB (2.5 MiB/s)"
+		);
+		progress.total = None;
+		assert_eq!(render_progress(&progress), "5.0 MiB (2.5 MiB/s)");
+	}
+}
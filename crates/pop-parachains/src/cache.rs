@@ -0,0 +1,408 @@
+// SPDX-License-Identifier: GPL-3.0
+use crate::errors::Error;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::{
+	cmp::Ordering,
+	collections::BTreeMap,
+	fs::{read_dir, read_to_string, remove_dir_all, remove_file, symlink_metadata, write},
+	path::{Path, PathBuf},
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The cache of binaries sourced when launching networks.
+pub struct Cache {
+	path: PathBuf,
+}
+
+impl Cache {
+	/// Creates a new instance of the cache at the provided location.
+	///
+	/// # Arguments
+	///
+	/// * `path` - path to the local cache
+	pub fn new(path: &Path) -> Self {
+		Self { path: path.to_path_buf() }
+	}
+
+	/// The location of the cache.
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	/// Returns all cached binaries, ordered by name and then by version, most recent first.
+	/// Binaries whose versions are not semantic versions (e.g. the commit from which a binary
+	/// was built) follow, most recently used first.
+	pub fn binaries(&self) -> Result<Vec<CachedBinary>, Error> {
+		let mut binaries = Vec::new();
+		if !self.path.exists() {
+			return Ok(binaries);
+		}
+		for entry in read_dir(&self.path)? {
+			let entry = entry?;
+			let file_name = entry.file_name().to_string_lossy().to_string();
+			// Ignore hidden files (metadata, partial downloads, sources), directories and symlinks
+			let metadata = symlink_metadata(entry.path())?;
+			if file_name.starts_with('.') || !metadata.is_file() {
+				continue;
+			}
+			let info = self.metadata(&file_name);
+			let (name, version) = match &info {
+				Some(info) => (info.name.clone(), info.version.clone()),
+				None => parse_name(&file_name),
+			};
+			binaries.push(CachedBinary {
+				name,
+				version,
+				path: entry.path(),
+				size: metadata.len(),
+				source: info.as_ref().and_then(|i| i.source.clone()),
+				last_used: info
+					.map(|i| UNIX_EPOCH + Duration::from_secs(i.last_used))
+					.or_else(|| metadata.modified().ok()),
+			});
+		}
+		binaries.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| compare_versions(b, a)));
+		Ok(binaries)
+	}
+
+	/// Removes all cached binaries except the `keep` most recent versions of each binary,
+	/// returning the binaries removed.
+	///
+	/// # Arguments
+	///
+	/// * `keep` - the number of versions of each binary to keep
+	pub fn prune(&self, keep: usize) -> Result<Vec<CachedBinary>, Error> {
+		let mut versions: BTreeMap<String, usize> = BTreeMap::new();
+		let mut removed = Vec::new();
+		// Binaries are ordered by version, most recent first
+		for binary in self.binaries()? {
+			let count = versions.entry(binary.name.clone()).or_default();
+			*count += 1;
+			if *count > keep {
+				self.remove(&binary)?;
+				removed.push(binary);
+			}
+		}
+		Ok(removed)
+	}
+
+	/// Removes all cached binaries, along with any partial downloads, source code, build
+	/// directories and cached GitHub api responses, returning the binaries removed. Networks
+	/// launched in detached mode are retained.
+	pub fn clean(&self) -> Result<Vec<CachedBinary>, Error> {
+		let removed = self.binaries()?;
+		for binary in &removed {
+			self.remove(binary)?;
+		}
+		if !self.path.exists() {
+			return Ok(removed);
+		}
+		for entry in read_dir(&self.path)? {
+			let path = entry?.path();
+			let metadata = symlink_metadata(&path)?;
+			let file_name = path.file_name().map(|n| n.to_string_lossy().to_string());
+			if metadata.is_symlink() {
+				// Symlinks to cached binaries
+				remove_file(&path)?;
+			} else if file_name.is_some_and(|n| n.starts_with('.')) {
+				match metadata.is_dir() {
+					true => remove_dir_all(&path)?,
+					false => remove_file(&path)?,
+				}
+			}
+		}
+		Ok(removed)
+	}
+
+	/// Records the source of a cached binary.
+	///
+	/// # Arguments
+	///
+	/// * `file_name` - the file name of the binary within the cache
+	/// * `name` - the name of the binary
+	/// * `version` - the version of the binary, if any
+	/// * `source` - the source of the binary (e.g. the url from which it was downloaded)
+	pub(crate) fn record(
+		&self,
+		file_name: &str,
+		name: &str,
+		version: Option<&str>,
+		source: &str,
+	) -> Result<(), Error> {
+		let metadata = Metadata {
+			name: name.into(),
+			version: version.map(|v| v.into()),
+			source: Some(source.into()),
+			last_used: now(),
+		};
+		self.write_metadata(file_name, &metadata)
+	}
+
+	/// Records that a cached binary has been used.
+	///
+	/// # Arguments
+	///
+	/// * `path` - the path to the binary
+	pub(crate) fn touch(&self, path: &Path) -> Result<(), Error> {
+		// Ignore binaries which are not cached
+		if path.parent() != Some(self.path.as_path()) || !path.exists() {
+			return Ok(());
+		}
+		let Some(file_name) = path.file_name().map(|n| n.to_string_lossy().to_string()) else {
+			return Ok(());
+		};
+		let metadata = match self.metadata(&file_name) {
+			Some(metadata) => Metadata { last_used: now(), ..metadata },
+			None => {
+				let (name, version) = parse_name(&file_name);
+				Metadata { name, version, source: None, last_used: now() }
+			},
+		};
+		self.write_metadata(&file_name, &metadata)
+	}
+
+	fn remove(&self, binary: &CachedBinary) -> Result<(), Error> {
+		remove_file(&binary.path)?;
+		if let Some(file_name) = binary.path.file_name().and_then(|n| n.to_str()) {
+			let metadata = self.metadata_path(file_name);
+			if metadata.exists() {
+				remove_file(metadata)?;
+			}
+		}
+		Ok(())
+	}
+
+	fn metadata(&self, file_name: &str) -> Option<Metadata> {
+		let contents = read_to_string(self.metadata_path(file_name)).ok()?;
+		serde_json::from_str(&contents).ok()
+	}
+
+	fn metadata_path(&self, file_name: &str) -> PathBuf {
+		self.path.join(format!(".{file_name}.json"))
+	}
+
+	fn write_metadata(&self, file_name: &str, metadata: &Metadata) -> Result<(), Error> {
+		let contents = serde_json::to_string_pretty(metadata)
+			.map_err(|err| Error::Config(format!("unable to serialize metadata: {err}")))?;
+		write(self.metadata_path(file_name), contents)?;
+		Ok(())
+	}
+}
+
+/// A binary within the cache.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CachedBinary {
+	/// The name of the binary.
+	pub name: String,
+	/// The version of the binary, if known.
+	pub version: Option<String>,
+	/// The location of the binary.
+	pub path: PathBuf,
+	/// The size of the binary, in bytes.
+	pub size: u64,
+	/// The source of the binary, if known.
+	pub source: Option<String>,
+	/// When the binary was last used, if known.
+	pub last_used: Option<SystemTime>,
+}
+
+// Metadata about a cached binary, persisted alongside the binary.
+#[derive(Deserialize, Serialize)]
+struct Metadata {
+	name: String,
+	version: Option<String>,
+	source: Option<String>,
+	last_used: u64,
+}
+
+fn now() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+// Determines the name and version of a binary from its file name (e.g. `polkadot-v1.7.0`).
+fn parse_name(file_name: &str) -> (String, Option<String>) {
+	match file_name.rsplit_once('-') {
+		Some((name, version))
+			if version
+				.strip_prefix('v')
+				.is_some_and(|v| v.starts_with(|c: char| c.is_ascii_digit())) =>
+			(name.into(), Some(version.into())),
+		_ => (file_name.into(), None),
+	}
+}
+
+// Compares binaries by version. Versions which are not semantic versions (e.g. the commit from
+// which a binary was built) cannot be ordered, so are instead compared by when the binaries were
+// last used, preceding any semantic versions.
+fn compare_versions(a: &CachedBinary, b: &CachedBinary) -> Ordering {
+	fn version(binary: &CachedBinary) -> Option<Version> {
+		let version = binary.version.as_deref()?;
+		Version::parse(version.strip_prefix('v').unwrap_or(version)).ok()
+	}
+	match (version(a), version(b)) {
+		(Some(x), Some(y)) => x.cmp(&y),
+		(Some(_), None) => Ordering::Greater,
+		(None, Some(_)) => Ordering::Less,
+		(None, None) => a.last_used.cmp(&b.last_used).then_with(|| a.version.cmp(&b.version)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+
+	fn cache() -> Result<(tempfile::TempDir, Cache)> {
+		let temp_dir = tempfile::tempdir()?;
+		let cache = Cache::new(temp_dir.path());
+		for file in [
+			"polkadot-v1.7.0",
+			"polkadot-v1.10.0",
+			"polkadot-v1.9.0",
+			"polkadot-parachain-v1.7.0",
+			"pop-node",
+		] {
+			write(temp_dir.path().join(file), file)?;
+		}
+		std::fs::create_dir(temp_dir.path().join("networks"))?;
+		std::fs::create_dir_all(temp_dir.path().join(".src/polkadot-sdk"))?;
+		write(temp_dir.path().join(".polkadot-v1.11.0.part"), "")?;
+		std::os::unix::fs::symlink(
+			temp_dir.path().join("polkadot-v1.10.0"),
+			temp_dir.path().join("polkadot-execute-worker"),
+		)?;
+		Ok((temp_dir, cache))
+	}
+
+	#[test]
+	fn test_binaries() -> Result<()> {
+		let (temp_dir, cache) = cache()?;
+		cache.record("pop-node", "pop-node", None, "https://github.com/r0gue-io/pop-node")?;
+		let binaries = cache.binaries()?;
+		assert_eq!(
+			binaries
+				.iter()
+				.map(|b| (b.name.as_str(), b.version.as_deref()))
+				.collect::<Vec<_>>(),
+			vec![
+				("polkadot", Some("v1.10.0")),
+				("polkadot", Some("v1.9.0")),
+				("polkadot", Some("v1.7.0")),
+				("polkadot-parachain", Some("v1.7.0")),
+				("pop-node", None),
+			]
+		);
+		assert_eq!(binaries[0].path, temp_dir.path().join("polkadot-v1.10.0"));
+		assert_eq!(binaries[0].size, "polkadot-v1.10.0".len() as u64);
+		assert_eq!(binaries[0].source, None);
+		assert!(binaries[0].last_used.is_some());
+		assert_eq!(binaries[4].source.as_deref(), Some("https://github.com/r0gue-io/pop-node"));
+		Ok(())
+	}
+
+	#[test]
+	fn test_binaries_empty_cache() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		assert!(Cache::new(&temp_dir.path().join("missing")).binaries()?.is_empty());
+		Ok(())
+	}
+
+	#[test]
+	fn test_prune() -> Result<()> {
+		let (temp_dir, cache) = cache()?;
+		cache.touch(&temp_dir.path().join("polkadot-v1.7.0"))?;
+		let removed = cache.prune(1)?;
+		assert_eq!(
+			removed.iter().map(|b| b.path.clone()).collect::<Vec<_>>(),
+			vec![temp_dir.path().join("polkadot-v1.9.0"), temp_dir.path().join("polkadot-v1.7.0")]
+		);
+		assert!(!temp_dir.path().join(".polkadot-v1.7.0.json").exists());
+		assert_eq!(cache.binaries()?.len(), 3);
+		assert!(cache.prune(1)?.is_empty());
+		Ok(())
+	}
+
+	#[test]
+	fn test_clean() -> Result<()> {
+		let (temp_dir, cache) = cache()?;
+		assert_eq!(cache.clean()?.len(), 5);
+		assert!(cache.binaries()?.is_empty());
+		let remaining: Vec<_> = read_dir(temp_dir.path())?
+			.map(|e| e.map(|e| e.file_name().to_string_lossy().to_string()))
+			.collect::<Result<_, _>>()?;
+		assert_eq!(remaining, vec!["networks"]);
+		Ok(())
+	}
+
+	#[test]
+	fn test_touch() -> Result<()> {
+		let (temp_dir, cache) = cache()?;
+		cache.record("polkadot-v1.7.0", "polkadot", Some("v1.7.0"), "https://example.com")?;
+		let metadata = cache.metadata("polkadot-v1.7.0").expect("metadata recorded");
+		cache.touch(&temp_dir.path().join("polkadot-v1.7.0"))?;
+		let touched = cache.metadata("polkadot-v1.7.0").expect("metadata recorded");
+		assert_eq!(touched.source, metadata.source);
+		assert!(touched.last_used >= metadata.last_used);
+		// Binaries outside of the cache are ignored
+		cache.touch(Path::new("/usr/bin/env"))?;
+		assert!(cache.metadata("env").is_none());
+		Ok(())
+	}
+
+	#[test]
+	fn test_parse_name() {
+		assert_eq!(parse_name("polkadot-v1.7.0"), ("polkadot".into(), Some("v1.7.0".into())));
+		assert_eq!(
+			parse_name("polkadot-parachain-v1.10.0"),
+			("polkadot-parachain".into(), Some("v1.10.0".into()))
+		);
+		assert_eq!(parse_name("pop-node"), ("pop-node".into(), None));
+		assert_eq!(parse_name("polkadot-vnext"), ("polkadot-vnext".into(), None));
+	}
+
+	#[test]
+	fn test_prune_builds() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let cache = Cache::new(temp_dir.path());
+		// Builds are versioned by commit, so are ordered by when they were last used
+		for (commit, last_used) in [("0f1e2d3", 2), ("3a4b5c6", 1)] {
+			let file_name = format!("polkadot-{commit}");
+			write(temp_dir.path().join(&file_name), &file_name)?;
+			let metadata = Metadata {
+				name: "polkadot".into(),
+				version: Some(commit.into()),
+				source: Some("https://github.com/paritytech/polkadot-sdk".into()),
+				last_used,
+			};
+			cache.write_metadata(&file_name, &metadata)?;
+		}
+		let removed = cache.prune(1)?;
+		assert_eq!(
+			removed.iter().map(|b| b.path.clone()).collect::<Vec<_>>(),
+			vec![temp_dir.path().join("polkadot-3a4b5c6")]
+		);
+		assert!(temp_dir.path().join("polkadot-0f1e2d3").exists());
+		Ok(())
+	}
+
+	#[test]
+	fn test_compare_versions() {
+		let binary = |version: Option<&str>, last_used: u64| CachedBinary {
+			name: "polkadot".into(),
+			version: version.map(String::from),
+			path: PathBuf::new(),
+			size: 0,
+			source: None,
+			last_used: Some(UNIX_EPOCH + Duration::from_secs(last_used)),
+		};
+		let compare = |a: (Option<&str>, u64), b: (Option<&str>, u64)| {
+			compare_versions(&binary(a.0, a.1), &binary(b.0, b.1))
+		};
+		assert_eq!(compare((Some("v1.10.0"), 0), (Some("v1.9.0"), 1)), Ordering::Greater);
+		assert_eq!(compare((Some("v1.7.0"), 0), (Some("v1.7.0"), 0)), Ordering::Equal);
+		assert_eq!(compare((None, 1), (Some("v1.7.0"), 0)), Ordering::Less);
+		// Commits are compared by when they were last used, rather than their digits
+		assert_eq!(compare((Some("3a4b5c6"), 1), (Some("0f1e2d3"), 2)), Ordering::Less);
+	}
+}
@@ -1,4 +1,5 @@
 use crate::templates;
+use std::time::{SystemTime, UNIX_EPOCH};
 use thiserror::Error;
 use zombienet_sdk::OrchestratorError;
 
@@ -22,9 +23,45 @@ pub enum Error {
 	#[error("HTTP error: {0}")]
 	HttpError(#[from] reqwest::Error),
 
+	#[error("Download error: {0}")]
+	Download(String),
+
+	#[error("Checksum mismatch for {url}: expected {expected}, found {actual}")]
+	ChecksumMismatch { url: String, expected: String, actual: String },
+
+	#[error("Unexpected content from {url}: {reason}")]
+	UnexpectedContent { url: String, reason: String },
+
 	#[error("Missing binary: {0}")]
 	MissingBinary(String),
 
+	#[error("Archive error: {0}")]
+	Archive(String),
+
+	#[error("Chain specification error: {0}")]
+	ChainSpec(String),
+
+	#[error(
+		"GitHub API rate limit exceeded, resetting in {} seconds. Set `GITHUB_TOKEN` to raise the limit",
+		resets_in(.reset)
+	)]
+	RateLimited { reset: u64 },
+
+	#[error("Offline: {0}")]
+	Offline(String),
+
+	#[error("Network not ready: {0}")]
+	NotReady(String),
+
+	#[error("RPC error: {0}")]
+	Rpc(String),
+
+	#[error("Subxt error: {0}")]
+	SubxtError(Box<subxt::Error>),
+
+	#[error("Runtime upgrade failed: {0}")]
+	Upgrade(String),
+
 	#[error("Configuration error: {0}")]
 	Config(String),
 
@@ -52,3 +89,20 @@ pub enum Error {
 	#[error("Template error: {0}")]
 	TemplateError(#[from] templates::Error),
 }
+
+// Boxed, as subxt errors would otherwise considerably increase the size of all results.
+impl From<subxt::Error> for Error {
+	fn from(error: subxt::Error) -> Self {
+		Self::SubxtError(Box::new(error))
+	}
+}
+
+// The number of seconds until a rate limit resets, from the time it resets (in seconds since the
+// unix epoch).
+fn resets_in(reset: &u64) -> u64 {
+	let now = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_secs())
+		.unwrap_or_default();
+	reset.saturating_sub(now)
+}
@@ -0,0 +1,272 @@
+// SPDX-License-Identifier: GPL-3.0
+use crate::{errors::Error, up::Status};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sp_core::{bytes::to_hex, twox_128};
+use std::collections::BTreeMap;
+use subxt::backend::rpc::{rpc_params, RpcClient};
+
+/// The number of storage keys requested at a time when downloading the state of a chain.
+const PAGE_SIZE: u32 = 1_000;
+/// The pallets whose state is retained from the local chain specification rather than forked, so
+/// that the forked chain can be authored by the collators of the local network.
+const LOCAL_PALLETS: [&str; 8] = [
+	"System",
+	"ParachainSystem",
+	"ParachainInfo",
+	"Aura",
+	"AuraExt",
+	"Session",
+	"CollatorSelection",
+	"Authorship",
+];
+/// The storage items of local pallets which are nevertheless forked.
+const FORKED_ITEMS: [(&str, &str); 1] = [("System", "Account")];
+
+/// A live chain whose state is forked into a local network.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fork {
+	/// The websocket uri of a node of the live chain.
+	pub uri: String,
+	/// The block at which the state is forked, as a number or hash. Defaults to the latest
+	/// finalized block.
+	pub block: Option<String>,
+}
+
+impl Fork {
+	/// Parses a fork, specified as `<ws-uri>[@block]` (e.g. `wss://rpc.example.com@1000`).
+	///
+	/// # Arguments
+	///
+	/// * `fork` - the fork to be parsed
+	pub fn parse(fork: &str) -> Result<Self, Error> {
+		let (uri, block) = match fork.rsplit_once('@') {
+			// Only a block number or hash is a block, so that any credentials within the uri are
+			// retained
+			Some((uri, block)) if block.parse::<u64>().is_ok() || is_hash(block) =>
+				(uri, Some(block.to_string())),
+			_ => (fork, None),
+		};
+		if !uri.contains("://") {
+			return Err(Error::Config(format!(
+				"invalid fork: {fork}, expected a websocket uri (e.g. wss://rpc.example.com)"
+			)));
+		}
+		Ok(Self { uri: uri.to_string(), block })
+	}
+
+	/// Downloads the state of the chain at the block to be forked, returning each storage item as
+	/// a hex encoded key and value. Child storage is not included.
+	///
+	/// # Arguments
+	///
+	/// * `status` - used to observe status updates
+	pub async fn state(&self, status: impl Status) -> Result<BTreeMap<String, String>, Error> {
+		#[derive(Deserialize)]
+		struct Changes {
+			changes: Vec<(String, Option<String>)>,
+		}
+
+		let client = RpcClient::from_url(&self.uri).await?;
+		let hash: String = match &self.block {
+			Some(hash) if is_hash(hash) => hash.clone(),
+			Some(number) => {
+				let number: u64 = number
+					.parse()
+					.map_err(|_| Error::Config(format!("invalid block: {number}")))?;
+				client
+					.request::<Option<String>>("chain_getBlockHash", rpc_params![number])
+					.await?
+					.ok_or(Error::Rpc(format!("block #{number} not found")))?
+			},
+			None => client.request("chain_getFinalizedHead", rpc_params![]).await?,
+		};
+		let mut state = BTreeMap::new();
+		let mut start: Option<String> = None;
+		loop {
+			let keys: Vec<String> = client
+				.request("state_getKeysPaged", rpc_params!["0x", PAGE_SIZE, &start, &hash])
+				.await?;
+			if keys.is_empty() {
+				break;
+			}
+			let changes: Vec<Changes> =
+				client.request("state_queryStorageAt", rpc_params![&keys, &hash]).await?;
+			for (key, value) in changes.into_iter().flat_map(|c| c.changes) {
+				if let Some(value) = value {
+					state.insert(key, value);
+				}
+			}
+			status.update(&format!("Downloaded {} storage items at {hash}", state.len()));
+			if keys.len() < PAGE_SIZE as usize {
+				break;
+			}
+			start = keys.last().cloned();
+		}
+		Ok(state)
+	}
+}
+
+/// Builds the raw chain specification of a forked parachain, by replacing the state of a local
+/// raw chain specification with the forked state. The state of the pallets required for the
+/// collators of the local network to author blocks is retained, with the parachain identifier
+/// updated.
+///
+/// # Arguments
+///
+/// * `chain_spec` - the local raw chain specification, generated by the parachain binary
+/// * `state` - the forked state, as hex encoded keys and values
+/// * `para_id` - the identifier of the parachain within the local network
+/// * `relay_chain` - the relay chain of the local network
+pub(crate) fn chain_spec(
+	mut chain_spec: Value,
+	state: BTreeMap<String, String>,
+	para_id: u32,
+	relay_chain: &str,
+) -> Result<Value, Error> {
+	let local: Vec<_> = LOCAL_PALLETS.iter().map(|pallet| storage_prefix(pallet, None)).collect();
+	let forked: Vec<_> = FORKED_ITEMS
+		.iter()
+		.map(|(pallet, item)| storage_prefix(pallet, Some(item)))
+		.collect();
+	let is_local = |key: &str| {
+		let key = key.to_lowercase();
+		local.iter().any(|p| key.starts_with(p)) && !forked.iter().any(|p| key.starts_with(p))
+	};
+	let top = chain_spec
+		.pointer_mut("/genesis/raw/top")
+		.and_then(|top| top.as_object_mut())
+		.ok_or(Error::ChainSpec("expected a raw chain specification".into()))?;
+	top.retain(|key, _| is_local(key));
+	for (key, value) in state.into_iter().filter(|(key, _)| !is_local(key)) {
+		top.insert(key, Value::String(value));
+	}
+	top.insert(
+		storage_prefix("ParachainInfo", Some("ParachainId")),
+		Value::String(to_hex(&para_id.to_le_bytes(), false)),
+	);
+	chain_spec["bootNodes"] = json!([]);
+	chain_spec["para_id"] = json!(para_id);
+	chain_spec["relay_chain"] = json!(relay_chain);
+	Ok(chain_spec)
+}
+
+// The hex encoded prefix of the storage of a pallet, or of one of its storage items.
+fn storage_prefix(pallet: &str, item: Option<&str>) -> String {
+	let mut prefix = twox_128(pallet.as_bytes()).to_vec();
+	if let Some(item) = item {
+		prefix.extend(twox_128(item.as_bytes()));
+	}
+	to_hex(&prefix, false)
+}
+
+// Whether the provided block is specified by its hash.
+fn is_hash(block: &str) -> bool {
+	block.len() == 66 &&
+		block.starts_with("0x") &&
+		block[2..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::utils::testing::serve_rpc;
+	use anyhow::Result;
+
+	const HASH: &str = "0x6b1c2ea2b6dca4d4c5b1b5b6b1c2ea2b6dca4d4c5b1b5b6b1c2ea2b6dca4d4c5";
+
+	#[test]
+	fn test_parse_fork() -> Result<()> {
+		assert_eq!(
+			Fork::parse("wss://rpc.example.com")?,
+			Fork { uri: "wss://rpc.example.com".into(), block: None }
+		);
+		assert_eq!(
+			Fork::parse("ws://127.0.0.1:9944@1000")?,
+			Fork { uri: "ws://127.0.0.1:9944".into(), block: Some("1000".into()) }
+		);
+		assert_eq!(
+			Fork::parse(&format!("wss://user@rpc.example.com@{HASH}"))?,
+			Fork { uri: "wss://user@rpc.example.com".into(), block: Some(HASH.into()) }
+		);
+		assert_eq!(Fork::parse("wss://user@rpc.example.com")?.block, None);
+		assert!(matches!(Fork::parse("rpc.example.com"), Err(Error::Config(_))));
+		Ok(())
+	}
+
+	#[test]
+	fn test_storage_prefix() {
+		assert_eq!(storage_prefix("System", None), "0x26aa394eea5630e07c48ae0c9558cef7");
+		assert_eq!(
+			storage_prefix("System", Some("Account")),
+			"0x26aa394eea5630e07c48ae0c9558cef7b99d880ec681799c0cf30e8886371da9"
+		);
+	}
+
+	#[tokio::test]
+	async fn test_state() -> Result<()> {
+		let uri = serve_rpc(|method, params| match method {
+			"chain_getBlockHash" if params[0] == 1000 => json!(HASH),
+			"chain_getBlockHash" => Value::Null,
+			"state_getKeysPaged" if params[2].is_null() && params[3] == HASH =>
+				json!(["0x01", "0x02", "0x03"]),
+			"state_getKeysPaged" => json!([]),
+			"state_queryStorageAt" => json!([{
+				"block": HASH,
+				"changes": [["0x01", "0xaa"], ["0x02", null], ["0x03", "0xcc"]]
+			}]),
+			_ => Value::Null,
+		});
+		let state = Fork::parse(&format!("{uri}@1000"))?.state(()).await?;
+		assert_eq!(
+			state,
+			BTreeMap::from([("0x01".into(), "0xaa".into()), ("0x03".into(), "0xcc".into())])
+		);
+		assert!(matches!(
+			Fork::parse(&format!("{uri}@1001"))?.state(()).await,
+			Err(Error::Rpc(message)) if message == "block #1001 not found"
+		));
+		Ok(())
+	}
+
+	#[test]
+	fn test_chain_spec() -> Result<()> {
+		let aura = format!("{}0001", storage_prefix("Aura", Some("Authorities")));
+		let account = format!("{}0002", storage_prefix("System", Some("Account")));
+		let para_id = storage_prefix("ParachainInfo", Some("ParachainId"));
+		let local = json!({
+			"name": "Local",
+			"bootNodes": ["/ip4/127.0.0.1/tcp/30333/p2p/12D3KooW"],
+			"para_id": 1000,
+			"relay_chain": "rococo",
+			"genesis": { "raw": { "top": {
+				":code": "0x00",
+				aura.clone(): "0xa1",
+				account.clone(): "0xb1",
+				para_id.clone(): "0xe8030000",
+				"0x99": "0x01"
+			}, "childrenDefault": {} } }
+		});
+		let state = BTreeMap::from([
+			(":code".to_string(), "0xff".to_string()),
+			(aura.clone(), "0xa2".to_string()),
+			(account.clone(), "0xb2".to_string()),
+		]);
+		let chain_spec = chain_spec(local, state, 2000, "rococo-local")?;
+		let top = &chain_spec["genesis"]["raw"]["top"];
+		// The runtime and state are forked, other than that required to author blocks locally
+		assert_eq!(top[":code"], "0xff");
+		assert_eq!(top[&aura], "0xa1");
+		assert_eq!(top[&account], "0xb2");
+		assert_eq!(top[&para_id], "0xd0070000");
+		assert!(top.get("0x99").is_none());
+		assert_eq!(chain_spec["para_id"], 2000);
+		assert_eq!(chain_spec["relay_chain"], "rococo-local");
+		assert_eq!(chain_spec["bootNodes"], json!([]));
+		assert!(matches!(
+			super::chain_spec(json!({}), BTreeMap::new(), 2000, "rococo-local"),
+			Err(Error::ChainSpec(_))
+		));
+		Ok(())
+	}
+}
@@ -1,19 +1,42 @@
 // SPDX-License-Identifier: GPL-3.0
 mod build;
+mod cache;
 mod errors;
+mod fork;
 mod generator;
+mod logs;
 mod new_pallet;
 mod new_parachain;
+mod readiness;
+mod registry;
+mod snapshot;
 mod templates;
 mod up;
+mod upgrade;
 mod utils;
+mod validate;
 
 pub use build::build_parachain;
+pub use cache::{Cache, CachedBinary};
+pub use fork::Fork;
+pub use logs::{Level, LogFilter, LogLine, NetworkLogs};
 pub use new_pallet::{create_pallet_template, TemplatePalletConfig};
 pub use new_parachain::instantiate_template_dir;
+pub use readiness::wait_for_blocks;
+pub use registry::{ChainInfo, NetworkInfo, NodeInfo, Registry};
+pub use snapshot::{create_snapshot, restore_snapshot};
 pub use templates::{Config, Provider, Template};
-pub use up::{Source, Status, Zombienet};
-pub use utils::git::{Git, GitHub, Release};
+pub use up::{
+	DownloadProgress, NetworkConfigBuilder, RetryPolicy, Source, SourceOptions, Status, Zombienet,
+};
+pub use upgrade::{upgrade_runtime, RuntimeUpgrade};
+pub use utils::git::{
+	Asset, Git, GitHub, GitHubClient, GitLab, GitProvider, Gitea, LocalRepository, Release,
+	Releases,
+};
 pub use utils::pallet_helpers::resolve_pallet_path;
+#[cfg(feature = "testing")]
+pub use utils::testing;
+pub use validate::{has_errors, validate, validate_toml, Diagnostic, Severity};
 // External exports
 pub use zombienet_sdk::NetworkNode;
@@ -0,0 +1,288 @@
+// SPDX-License-Identifier: GPL-3.0
+use crate::{errors::Error, registry::NetworkInfo};
+use regex::Regex;
+use std::{
+	fs::File,
+	io::{Read, Seek, SeekFrom},
+	path::PathBuf,
+};
+
+/// The level of a log line, from the most to the least severe.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Level {
+	/// An error.
+	Error,
+	/// A warning.
+	Warn,
+	/// Informational.
+	Info,
+	/// Debugging.
+	Debug,
+	/// Tracing, the most verbose.
+	Trace,
+}
+
+impl Level {
+	/// The names of the levels, as used within log lines.
+	pub const NAMES: [&'static str; 5] = ["error", "warn", "info", "debug", "trace"];
+
+	/// Parses a level from its name, ignoring case.
+	///
+	/// # Arguments
+	///
+	/// * `level` - the name of the level (e.g. "warn")
+	pub fn parse(level: &str) -> Option<Self> {
+		match level.to_lowercase().as_str() {
+			"error" => Some(Self::Error),
+			"warn" | "warning" => Some(Self::Warn),
+			"info" => Some(Self::Info),
+			"debug" => Some(Self::Debug),
+			"trace" => Some(Self::Trace),
+			_ => None,
+		}
+	}
+
+	// Determines the level of a log line, which follows the timestamp (e.g.
+	// `2024-04-04 10:00:00.000  INFO main sc_cli::runner: ...`).
+	fn of(line: &str) -> Option<Self> {
+		line.split_whitespace().take(4).find_map(|token| match token {
+			"ERROR" => Some(Self::Error),
+			"WARN" => Some(Self::Warn),
+			"INFO" => Some(Self::Info),
+			"DEBUG" => Some(Self::Debug),
+			"TRACE" => Some(Self::Trace),
+			_ => None,
+		})
+	}
+}
+
+/// A filter for the log lines of nodes.
+#[derive(Clone, Debug, Default)]
+pub struct LogFilter {
+	/// The least severe level of lines to be included.
+	level: Option<Level>,
+	/// A pattern which lines must match to be included.
+	pattern: Option<Regex>,
+}
+
+impl LogFilter {
+	/// Creates a new filter, which includes all lines.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Only includes lines at the provided level or more severe. Lines without a level, such as
+	/// the continuation of a multi-line message, are always included.
+	///
+	/// # Arguments
+	///
+	/// * `level` - the least severe level to be included
+	pub fn with_level(mut self, level: Level) -> Self {
+		self.level = Some(level);
+		self
+	}
+
+	/// Only includes lines matching the provided regular expression.
+	///
+	/// # Arguments
+	///
+	/// * `pattern` - the regular expression to be matched
+	pub fn with_pattern(mut self, pattern: &str) -> Result<Self, Error> {
+		let pattern = Regex::new(pattern)
+			.map_err(|e| Error::Config(format!("invalid log filter `{pattern}`: {e}")))?;
+		self.pattern = Some(pattern);
+		Ok(self)
+	}
+
+	/// Whether a log line is included by the filter.
+	///
+	/// # Arguments
+	///
+	/// * `line` - the log line
+	pub fn matches(&self, line: &str) -> bool {
+		if let Some(level) = self.level {
+			if Level::of(line).is_some_and(|l| l > level) {
+				return false;
+			}
+		}
+		self.pattern.as_ref().map_or(true, |p| p.is_match(line))
+	}
+}
+
+/// A line logged by a node.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LogLine {
+	/// The name of the node.
+	pub node: String,
+	/// The line logged, without any line ending.
+	pub line: String,
+}
+
+/// The logs of the nodes of a launched network, read incrementally so that they can be followed
+/// as they are written.
+pub struct NetworkLogs {
+	/// The nodes, along with the location of their log and how much of it has been read.
+	nodes: Vec<NodeLog>,
+	filter: LogFilter,
+}
+
+// The log of a node.
+struct NodeLog {
+	name: String,
+	path: PathBuf,
+	/// The position up to which the log has been read.
+	position: u64,
+	/// Any trailing line which has not yet been completely written.
+	partial: Vec<u8>,
+}
+
+impl NetworkLogs {
+	/// Opens the logs of the nodes of a network, read from the network base directory.
+	///
+	/// # Arguments
+	///
+	/// * `network` - the launched network
+	/// * `nodes` - the names of the nodes whose logs are to be read, or all nodes if empty
+	/// * `filter` - the filter for the lines to be read
+	pub fn new(network: &NetworkInfo, nodes: &[String], filter: LogFilter) -> Result<Self, Error> {
+		if let Some(unknown) = nodes.iter().find(|n| !network.nodes().any(|node| node.name == **n))
+		{
+			return Err(Error::Config(format!(
+				"unknown node: {unknown}, expected one of: {}",
+				network.nodes().map(|n| n.name.as_str()).collect::<Vec<_>>().join(", ")
+			)));
+		}
+		let nodes = network
+			.nodes()
+			.filter(|node| nodes.is_empty() || nodes.contains(&node.name))
+			.map(|node| NodeLog {
+				name: node.name.clone(),
+				// Networks registered by earlier versions do not record the location of logs
+				path: match node.log_path.as_os_str().is_empty() {
+					true => network.base_dir.join(&node.name).join(format!("{}.log", node.name)),
+					false => node.log_path.clone(),
+				},
+				position: 0,
+				partial: Vec::new(),
+			})
+			.collect();
+		Ok(Self { nodes, filter })
+	}
+
+	/// The names of the nodes whose logs are read.
+	pub fn nodes(&self) -> impl Iterator<Item = &str> {
+		self.nodes.iter().map(|n| n.name.as_str())
+	}
+
+	/// Reads any lines written since the logs were last read, grouped by node. Logs which do not
+	/// yet exist are skipped, as nodes may still be starting.
+	pub fn read(&mut self) -> Result<Vec<LogLine>, Error> {
+		let mut lines = Vec::new();
+		for node in &mut self.nodes {
+			let Ok(mut file) = File::open(&node.path) else {
+				continue;
+			};
+			// The log was truncated, so is read afresh
+			if file.metadata()?.len() < node.position {
+				node.position = 0;
+				node.partial.clear();
+			}
+			file.seek(SeekFrom::Start(node.position))?;
+			let mut contents = std::mem::take(&mut node.partial);
+			node.position += file.read_to_end(&mut contents)? as u64;
+			let complete = contents.iter().rposition(|b| *b == b'\n').map_or(0, |i| i + 1);
+			node.partial = contents.split_off(complete);
+			for line in String::from_utf8_lossy(&contents).lines() {
+				if self.filter.matches(line) {
+					lines.push(LogLine { node: node.name.clone(), line: line.to_string() });
+				}
+			}
+		}
+		Ok(lines)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::registry::{
+		testing::{self, NetworkBuilder},
+		NodeInfo,
+	};
+	use anyhow::Result;
+	use std::{fs::OpenOptions, io::Write, path::Path};
+
+	fn network(base_dir: &Path) -> NetworkInfo {
+		// Without a recorded log path, logs are read from within the base directory
+		let node = |name: &str| NodeInfo { log_path: PathBuf::new(), ..testing::node(name, 9944) };
+		NetworkBuilder::new("zombie")
+			.base_dir(base_dir)
+			.relay_node(node("alice"))
+			.relay_node(node("bob"))
+			.parachain(None, 2000, vec![node("collator")])
+			.build()
+	}
+
+	fn append(network: &NetworkInfo, node: &str, contents: &str) -> Result<()> {
+		let dir = network.base_dir.join(node);
+		std::fs::create_dir_all(&dir)?;
+		let mut file = OpenOptions::new()
+			.create(true)
+			.append(true)
+			.open(dir.join(format!("{node}.log")))?;
+		file.write_all(contents.as_bytes())?;
+		Ok(())
+	}
+
+	#[test]
+	fn test_log_filter() -> Result<()> {
+		const WARN: &str = "2024-04-04 10:00:00.000  WARN tokio-runtime-worker sync: slow";
+		const INFO: &str = "2024-04-04 10:00:00.000  INFO main sc_cli::runner: Imported #1";
+		let filter = LogFilter::new().with_level(Level::Warn);
+		assert!(filter.matches(WARN));
+		assert!(!filter.matches(INFO));
+		assert!(filter.matches("  continued"));
+		let filter = LogFilter::new().with_pattern(r"Imported #\d+")?;
+		assert!(filter.matches(INFO));
+		assert!(!filter.matches(WARN));
+		assert!(matches!(LogFilter::new().with_pattern("("), Err(Error::Config(_))));
+		assert_eq!(Level::parse("WARNING"), Some(Level::Warn));
+		assert_eq!(Level::parse("verbose"), None);
+		Ok(())
+	}
+
+	#[test]
+	fn test_read_logs_incrementally() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let network = network(temp_dir.path());
+		let mut logs = NetworkLogs::new(&network, &[], LogFilter::new())?;
+		assert_eq!(logs.nodes().collect::<Vec<_>>(), ["alice", "bob", "collator"]);
+		// Logs which do not exist yet are skipped
+		assert!(logs.read()?.is_empty());
+
+		append(&network, "alice", "one\ntw")?;
+		append(&network, "collator", "three\n")?;
+		let line = |node: &str, line: &str| LogLine { node: node.into(), line: line.into() };
+		assert_eq!(logs.read()?, [line("alice", "one"), line("collator", "three")]);
+		// Partially written lines are read once complete
+		append(&network, "alice", "o\n")?;
+		assert_eq!(logs.read()?, [line("alice", "two")]);
+		assert!(logs.read()?.is_empty());
+		Ok(())
+	}
+
+	#[test]
+	fn test_read_logs_of_nodes() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let network = network(temp_dir.path());
+		append(&network, "alice", "alice\n")?;
+		append(&network, "bob", "bob\n")?;
+		let mut logs = NetworkLogs::new(&network, &["bob".into()], LogFilter::new())?;
+		assert_eq!(logs.read()?, [LogLine { node: "bob".into(), line: "bob".into() }]);
+		assert!(matches!(
+			NetworkLogs::new(&network, &["dave".into()], LogFilter::new()),
+			Err(Error::Config(message)) if message == "unknown node: dave, expected one of: alice, bob, collator"
+		));
+		Ok(())
+	}
+}
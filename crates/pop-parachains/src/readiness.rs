@@ -0,0 +1,174 @@
+// SPDX-License-Identifier: GPL-3.0
+use crate::{
+	errors::Error,
+	registry::{ChainInfo, NetworkInfo},
+	up::Status,
+};
+use futures::{future::join_all, StreamExt};
+use std::{cell::Cell, time::Duration};
+use subxt::{
+	backend::rpc::{rpc_params, RpcClient},
+	config::Header as _,
+	Config, PolkadotConfig,
+};
+use tokio::time::sleep;
+
+/// The interval at which subscribing to the finalized blocks of a parachain is retried, as its
+/// nodes may not yet be serving RPC requests.
+const RETRY_INTERVAL: Duration = Duration::from_secs(2);
+
+type Header = <PolkadotConfig as Config>::Header;
+
+/// Waits until every parachain of a network has finalized at least the provided number of
+/// blocks, subscribing to the finalized blocks of their collators. The progress of each parachain
+/// is reported as blocks are finalized.
+///
+/// # Arguments
+///
+/// * `network` - the launched network
+/// * `blocks` - the number of blocks each parachain must have finalized
+/// * `timeout` - the maximum time to wait for the network to be ready
+/// * `status` - used to observe status updates
+pub async fn wait_for_blocks(
+	network: &NetworkInfo,
+	blocks: u64,
+	timeout: Duration,
+	status: impl Status,
+) -> Result<(), Error> {
+	let finalized: Vec<Cell<Option<u64>>> = vec![Cell::new(None); network.parachains.len()];
+	let ready =
+		join_all(network.parachains.iter().zip(&finalized).map(|(parachain, finalized)| {
+			wait_for_chain(parachain, blocks, move |number| {
+				finalized.set(Some(number));
+				status.update(&format!(
+					"{}: finalized block #{number} of {blocks}",
+					parachain.label()
+				));
+			})
+		}));
+	if tokio::time::timeout(timeout, ready).await.is_ok() {
+		return Ok(());
+	}
+	let pending: Vec<_> = network
+		.parachains
+		.iter()
+		.zip(&finalized)
+		.filter(|(_, f)| !f.get().is_some_and(|f| f >= blocks))
+		.map(|(p, f)| match f.get() {
+			Some(f) => format!("{} (finalized block #{f})", p.label()),
+			None => format!("{} (no blocks finalized)", p.label()),
+		})
+		.collect();
+	Err(Error::NotReady(format!(
+		"parachains did not finalize {blocks} blocks within {} seconds: {}",
+		timeout.as_secs(),
+		pending.join(", ")
+	)))
+}
+
+// Waits until a chain has finalized the provided number of blocks, subscribing to the finalized
+// blocks of the first of its nodes to accept and providing the number of each to `finalized`.
+async fn wait_for_chain(chain: &ChainInfo, blocks: u64, finalized: impl Fn(u64)) {
+	loop {
+		for node in &chain.nodes {
+			let Ok(client) = RpcClient::from_url(&node.ws_uri).await else {
+				continue;
+			};
+			let Ok(mut headers) = client
+				.subscribe::<Header>(
+					"chain_subscribeFinalizedHeads",
+					rpc_params![],
+					"chain_unsubscribeFinalizedHeads",
+				)
+				.await
+			else {
+				continue;
+			};
+			while let Some(Ok(header)) = StreamExt::next(&mut headers).await {
+				let number = header.number().into();
+				finalized(number);
+				if number >= blocks {
+					return;
+				}
+			}
+		}
+		// The nodes may not yet be serving RPC requests, or the subscription may have ended
+		sleep(RETRY_INTERVAL).await;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		registry::{
+			testing::{node, NetworkBuilder},
+			NodeInfo,
+		},
+		utils::testing::serve_rpc,
+	};
+	use anyhow::Result;
+	use serde_json::{json, Value};
+
+	fn network(parachains: &[(u32, &str)]) -> NetworkInfo {
+		parachains
+			.iter()
+			.fold(NetworkBuilder::new("zombie"), |network, (id, ws_uri)| {
+				let collator =
+					NodeInfo { ws_uri: ws_uri.to_string(), ..node(&format!("collator-{id}"), 0) };
+				network.parachain(None, *id, vec![collator])
+			})
+			.build()
+	}
+
+	// Serves a chain which finalizes the blocks within the provided range.
+	fn chain(blocks: std::ops::RangeInclusive<u64>) -> String {
+		serve_rpc(move |method, _| match method {
+			"chain_subscribeFinalizedHeads" => blocks
+				.clone()
+				.map(|number| {
+					json!({
+						"parentHash": format!("0x{}", "00".repeat(32)),
+						"number": format!("0x{number:x}"),
+						"stateRoot": format!("0x{}", "00".repeat(32)),
+						"extrinsicsRoot": format!("0x{}", "00".repeat(32)),
+						"digest": { "logs": [] }
+					})
+				})
+				.collect(),
+			_ => Value::Null,
+		})
+	}
+
+	#[derive(Clone, Copy)]
+	struct Updates<'a>(&'a std::sync::Mutex<Vec<String>>);
+
+	impl Status for Updates<'_> {
+		fn update(&self, status: &str) {
+			self.0.lock().unwrap().push(status.into());
+		}
+	}
+
+	#[tokio::test]
+	async fn test_wait_for_blocks() -> Result<()> {
+		let network = network(&[(2000, &chain(0..=5)), (2001, &chain(2..=5))]);
+		let updates = std::sync::Mutex::new(Vec::new());
+		wait_for_blocks(&network, 2, Duration::from_secs(30), Updates(&updates)).await?;
+		let updates = updates.into_inner().unwrap();
+		assert!(updates.contains(&"para_id: 2000: finalized block #2 of 2".to_string()));
+		assert!(!updates.contains(&"para_id: 2000: finalized block #3 of 2".to_string()));
+		assert_eq!(updates.iter().filter(|u| u.starts_with("para_id: 2001")).count(), 1);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_wait_for_blocks_times_out() {
+		let network = network(&[(2000, &chain(0..=0)), (2001, "ws://127.0.0.1:1")]);
+		assert!(matches!(
+			wait_for_blocks(&network, 1, Duration::from_secs(1), ()).await,
+			Err(Error::NotReady(message)) if message.ends_with(
+				"para_id: 2000 (finalized block #0), para_id: 2001 (no blocks finalized)"
+			)
+		));
+	}
+}
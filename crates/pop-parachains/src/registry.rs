@@ -0,0 +1,618 @@
+// SPDX-License-Identifier: GPL-3.0
+use crate::{errors::Error, utils::rpc::http_uri};
+use duct::cmd;
+use serde::{Deserialize, Serialize};
+use std::{
+	fs::{create_dir_all, read_dir, read_to_string, remove_file, write},
+	path::{Path, PathBuf},
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::time::sleep;
+use zombienet_sdk::{Network, NetworkNode};
+use zombienet_support::fs::local::LocalFileSystem;
+
+const REGISTRY: &str = "networks";
+
+/// A registry of networks launched in detached mode, persisted within the cache.
+pub struct Registry {
+	path: PathBuf,
+}
+
+impl Registry {
+	/// Creates a new registry, located within the provided cache.
+	///
+	/// # Arguments
+	///
+	/// * `cache` - path to the local cache
+	pub fn new(cache: &Path) -> Self {
+		Self { path: cache.join(REGISTRY) }
+	}
+
+	/// The location of the registry.
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	/// Registers a network, replacing any existing entry with the same identifier.
+	///
+	/// # Arguments
+	///
+	/// * `network` - the network to be registered
+	pub fn register(&self, network: &NetworkInfo) -> Result<PathBuf, Error> {
+		create_dir_all(&self.path)?;
+		let path = self.entry(&network.id);
+		write(&path, network.to_json()?)?;
+		Ok(path)
+	}
+
+	/// Returns all registered networks, ordered by launch time.
+	pub fn networks(&self) -> Result<Vec<NetworkInfo>, Error> {
+		let mut networks = Vec::new();
+		if !self.path.exists() {
+			return Ok(networks);
+		}
+		for entry in read_dir(&self.path)? {
+			let path = entry?.path();
+			if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+				continue;
+			}
+			let contents = read_to_string(&path)?;
+			match serde_json::from_str::<NetworkInfo>(&contents) {
+				Ok(network) => networks.push(network),
+				// Ignore entries which cannot be read, as they may have been written by another
+				// version
+				Err(_) => continue,
+			}
+		}
+		networks.sort_by_key(|n| n.launched);
+		Ok(networks)
+	}
+
+	/// Returns the registered network with the provided identifier, if any.
+	///
+	/// # Arguments
+	///
+	/// * `id` - the identifier of the network
+	pub fn get(&self, id: &str) -> Result<Option<NetworkInfo>, Error> {
+		Ok(self.networks()?.into_iter().find(|n| n.id == id))
+	}
+
+	/// Removes the network with the provided identifier from the registry.
+	///
+	/// # Arguments
+	///
+	/// * `id` - the identifier of the network
+	pub fn remove(&self, id: &str) -> Result<(), Error> {
+		let path = self.entry(id);
+		if path.exists() {
+			remove_file(path)?;
+		}
+		Ok(())
+	}
+
+	/// Removes any networks which are no longer running from the registry, where neither the
+	/// supervising process nor any node process remains, returning the networks removed.
+	pub fn prune(&self) -> Result<Vec<NetworkInfo>, Error> {
+		let mut removed = Vec::new();
+		for network in self.networks()? {
+			if !network.is_running() && !network.nodes().any(|n| n.is_running()) {
+				self.remove(&network.id)?;
+				removed.push(network);
+			}
+		}
+		Ok(removed)
+	}
+
+	fn entry(&self, id: &str) -> PathBuf {
+		self.path.join(format!("{id}.json"))
+	}
+}
+
+/// Information about a launched network.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct NetworkInfo {
+	/// The identifier of the network, derived from its base directory.
+	pub id: String,
+	/// The base directory of the network, containing node data and logs.
+	pub base_dir: PathBuf,
+	/// The identifier of the process supervising the network.
+	pub pid: u32,
+	/// When the process supervising the network was started, as reported by `ps`, used to
+	/// determine whether the process identifier has since been reused by another process.
+	#[serde(default)]
+	pub started: Option<String>,
+	/// When the network was launched, as seconds since the unix epoch.
+	pub launched: u64,
+	/// The relay chain of the network.
+	pub relay_chain: ChainInfo,
+	/// The parachains of the network.
+	pub parachains: Vec<ChainInfo>,
+}
+
+impl NetworkInfo {
+	/// Collects information about a spawned network.
+	///
+	/// # Arguments
+	///
+	/// * `network` - the spawned network
+	/// * `pid` - the identifier of the process supervising the network
+	pub fn new(network: &Network<LocalFileSystem>, pid: u32) -> Result<Self, Error> {
+		let base_dir = PathBuf::from(
+			network.base_dir().ok_or(Error::Config("expected network base_dir".into()))?,
+		);
+		let id = base_dir
+			.file_name()
+			.and_then(|n| n.to_str())
+			.ok_or(Error::Config("the network base_dir is invalid".into()))?
+			.to_string();
+		let launched = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+
+		let relay_chain = network.relaychain();
+		let relay_chain = ChainInfo {
+			chain: Some(relay_chain.chain().to_string()),
+			para_id: None,
+			nodes: Self::node_info(relay_chain.nodes(), &base_dir),
+		};
+		let mut parachains: Vec<_> = network
+			.parachains()
+			.into_iter()
+			.map(|p| ChainInfo {
+				chain: p.chain_id().map(|c| c.to_string()),
+				para_id: Some(p.para_id()),
+				nodes: Self::node_info(p.collators(), &base_dir),
+			})
+			.collect();
+		parachains.sort_by_key(|p| p.para_id);
+
+		Ok(Self { id, base_dir, pid, started: started(pid), launched, relay_chain, parachains })
+	}
+
+	fn node_info(mut nodes: Vec<&NetworkNode>, base_dir: &Path) -> Vec<NodeInfo> {
+		nodes.sort_by_key(|n| n.name());
+		nodes
+			.into_iter()
+			.map(|node| {
+				let name = node.name();
+				let command = node.spec().command().to_string();
+				let args: Vec<String> = node.args().iter().map(|a| a.to_string()).collect();
+				let pid = node_pid(base_dir, name, &command);
+				NodeInfo {
+					name: name.to_string(),
+					pid,
+					started: pid.and_then(started),
+					ws_uri: node.ws_uri().to_string(),
+					rpc_uri: http_uri(node.ws_uri()),
+					prometheus_port: prometheus_port(&args),
+					log_path: base_dir.join(name).join(format!("{name}.log")),
+					command,
+					args,
+				}
+			})
+			.collect()
+	}
+
+	/// The network information as JSON, as used by the registry and for consumption by other
+	/// tools.
+	pub fn to_json(&self) -> Result<String, Error> {
+		serde_json::to_string_pretty(self)
+			.map_err(|err| Error::Config(format!("unable to serialize network: {err}")))
+	}
+
+	/// All nodes of the network, starting with those of the relay chain.
+	pub fn nodes(&self) -> impl Iterator<Item = &NodeInfo> {
+		self.relay_chain
+			.nodes
+			.iter()
+			.chain(self.parachains.iter().flat_map(|p| p.nodes.iter()))
+	}
+
+	/// Whether the process supervising the network is still running.
+	pub fn is_running(&self) -> bool {
+		is_running(self.pid, self.started.as_deref())
+	}
+
+	/// Terminates the network, by first signalling the supervising process to shut the network
+	/// down gracefully and then terminating any remaining node processes.
+	pub async fn terminate(&self) -> Result<(), Error> {
+		if self.is_running() {
+			signal(self.pid, "INT")?;
+			// Allow time for the supervising process to shut down its nodes
+			for _ in 0..20 {
+				if !self.is_running() {
+					break;
+				}
+				sleep(Duration::from_millis(500)).await;
+			}
+		}
+		for node in self.nodes().filter(|n| n.is_running()) {
+			if let Some(pid) = node.pid {
+				signal(pid, "TERM")?;
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Information about a chain within a launched network.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ChainInfo {
+	/// The name of the chain, if known.
+	pub chain: Option<String>,
+	/// The identifier of the parachain, if applicable.
+	pub para_id: Option<u32>,
+	/// The nodes of the chain.
+	pub nodes: Vec<NodeInfo>,
+}
+
+impl ChainInfo {
+	/// A label for the chain, suitable for display.
+	pub fn label(&self) -> String {
+		match (&self.chain, self.para_id) {
+			(Some(chain), Some(para_id)) => format!("{chain}: {para_id}"),
+			(None, Some(para_id)) => format!("para_id: {para_id}"),
+			(Some(chain), None) => chain.clone(),
+			(None, None) => "unknown".into(),
+		}
+	}
+}
+
+/// Information about a node within a launched network.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct NodeInfo {
+	/// The name of the node.
+	pub name: String,
+	/// The identifier of the node process, if it could be determined.
+	pub pid: Option<u32>,
+	/// When the node process was started, as reported by `ps`, used to determine whether the
+	/// process identifier has since been reused by another process.
+	#[serde(default)]
+	pub started: Option<String>,
+	/// The websocket uri of the node.
+	pub ws_uri: String,
+	/// The HTTP uri of the node's RPC server, which shares its port with the websocket uri.
+	#[serde(default)]
+	pub rpc_uri: String,
+	/// The port on which the node exposes Prometheus metrics, if enabled.
+	#[serde(default)]
+	pub prometheus_port: Option<u16>,
+	/// The location of the node's log file.
+	#[serde(default)]
+	pub log_path: PathBuf,
+	/// The command used to launch the node.
+	#[serde(default)]
+	pub command: String,
+	/// The arguments provided to the command.
+	#[serde(default)]
+	pub args: Vec<String>,
+}
+
+impl NodeInfo {
+	/// Whether the node process is still running.
+	pub fn is_running(&self) -> bool {
+		self.pid.is_some_and(|pid| is_running(pid, self.started.as_deref()))
+	}
+}
+
+// The Prometheus port of a node, from the arguments it was launched with.
+fn prometheus_port(args: &[String]) -> Option<u16> {
+	args.iter().enumerate().find_map(|(i, arg)| match arg.split_once('=') {
+		Some(("--prometheus-port", port)) => port.parse().ok(),
+		_ if arg == "--prometheus-port" => args.get(i + 1)?.parse().ok(),
+		_ => None,
+	})
+}
+
+// Locates the process of a node, based on it running the node command with its data located
+// within the network base directory. Other processes referring to the directory (e.g. `tail -f`
+// of the node log) are ignored.
+fn node_pid(base_dir: &Path, name: &str, command: &str) -> Option<u32> {
+	let binary = Path::new(command).file_name()?.to_string_lossy();
+	let pattern = format!(
+		"^([^ ]*/)?{} .*{}",
+		regex::escape(&binary),
+		regex::escape(&format!("{}/{name}/", base_dir.display()))
+	);
+	let output = cmd("pgrep", vec!["-f", &pattern]).stderr_null().unchecked().read().ok()?;
+	output.lines().filter_map(|l| l.trim().parse::<u32>().ok()).next()
+}
+
+// When a process was started, as reported by `ps`, if it is running.
+fn started(pid: u32) -> Option<String> {
+	let output =
+		cmd("ps", vec!["-o".to_string(), "lstart=".to_string(), "-p".to_string(), pid.to_string()])
+			.stderr_null()
+			.unchecked()
+			.read()
+			.ok()?;
+	Some(output.trim().to_string()).filter(|s| !s.is_empty())
+}
+
+// Whether a process is running, provided it is the process started at the recorded time rather
+// than another process which has since been assigned the same identifier. Processes without a
+// recorded start time cannot be identified, so are not considered to be running.
+fn is_running(pid: u32, started_at: Option<&str>) -> bool {
+	// Guard against identifiers which `kill` would interpret as a process group
+	if pid == 0 || pid > i32::MAX as u32 {
+		return false;
+	}
+	let alive = cmd("kill", vec!["-0".to_string(), pid.to_string()])
+		.stdout_null()
+		.stderr_null()
+		.unchecked()
+		.run()
+		.map_or(false, |output| output.status.success());
+	alive && started_at.is_some_and(|s| started(pid).as_deref() == Some(s))
+}
+
+fn signal(pid: u32, signal: &str) -> Result<(), Error> {
+	if pid == 0 || pid > i32::MAX as u32 {
+		return Err(Error::Config(format!("invalid process identifier: {pid}")));
+	}
+	cmd("kill", vec![format!("-{signal}"), pid.to_string()])
+		.stdout_null()
+		.stderr_null()
+		.unchecked()
+		.run()?;
+	Ok(())
+}
+
+/// Helpers for building network information within tests.
+#[cfg(test)]
+pub(crate) mod testing {
+	use super::*;
+
+	/// Builds information about a network, whose relay chain is `rococo-local`.
+	pub(crate) struct NetworkBuilder(NetworkInfo);
+
+	impl NetworkBuilder {
+		/// Starts building a network without any nodes or parachains.
+		///
+		/// # Arguments
+		///
+		/// * `id` - the identifier of the network, also used as its base directory within `/tmp`
+		pub(crate) fn new(id: &str) -> Self {
+			Self(NetworkInfo {
+				id: id.into(),
+				base_dir: PathBuf::from("/tmp").join(id),
+				pid: u32::MAX,
+				started: None,
+				launched: 0,
+				relay_chain: ChainInfo {
+					chain: Some("rococo-local".into()),
+					para_id: None,
+					nodes: Vec::new(),
+				},
+				parachains: Vec::new(),
+			})
+		}
+
+		/// Sets the base directory of the network.
+		pub(crate) fn base_dir(mut self, base_dir: &Path) -> Self {
+			self.0.base_dir = base_dir.to_path_buf();
+			self
+		}
+
+		/// Sets when the network was launched, as seconds since the unix epoch.
+		pub(crate) fn launched(mut self, launched: u64) -> Self {
+			self.0.launched = launched;
+			self
+		}
+
+		/// Adds a node to the relay chain.
+		pub(crate) fn relay_node(mut self, node: NodeInfo) -> Self {
+			self.0.relay_chain.nodes.push(node);
+			self
+		}
+
+		/// Adds a parachain with the provided nodes.
+		///
+		/// # Arguments
+		///
+		/// * `chain` - the name of the chain, if known
+		/// * `para_id` - the identifier of the parachain
+		/// * `nodes` - the nodes of the parachain
+		pub(crate) fn parachain(
+			mut self,
+			chain: Option<&str>,
+			para_id: u32,
+			nodes: Vec<NodeInfo>,
+		) -> Self {
+			self.0.parachains.push(ChainInfo {
+				chain: chain.map(String::from),
+				para_id: Some(para_id),
+				nodes,
+			});
+			self
+		}
+
+		/// Returns the network built.
+		pub(crate) fn build(self) -> NetworkInfo {
+			self.0
+		}
+	}
+
+	/// Returns information about a node of the `polkadot` binary, serving RPC requests at the
+	/// provided port and metrics at the port thereafter.
+	///
+	/// # Arguments
+	///
+	/// * `name` - the name of the node
+	/// * `port` - the RPC port of the node
+	pub(crate) fn node(name: &str, port: u16) -> NodeInfo {
+		NodeInfo {
+			name: name.into(),
+			pid: None,
+			started: None,
+			ws_uri: format!("ws://127.0.0.1:{port}"),
+			rpc_uri: format!("http://127.0.0.1:{port}"),
+			prometheus_port: Some(port + 1),
+			log_path: PathBuf::from(format!("/tmp/zombie/{name}/{name}.log")),
+			command: "polkadot".into(),
+			args: vec!["--prometheus-port".into(), (port + 1).to_string()],
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{
+		testing::{node, NetworkBuilder},
+		*,
+	};
+	use anyhow::Result;
+
+	fn network(id: &str, launched: u64) -> NetworkInfo {
+		NetworkBuilder::new(id)
+			.launched(launched)
+			.relay_node(node("alice", 9944))
+			.parachain(Some("asset-hub-rococo-local"), 1000, vec![node("asset-hub", 9988)])
+			.build()
+	}
+
+	#[test]
+	fn test_register_and_remove() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let registry = Registry::new(temp_dir.path());
+		assert!(registry.networks()?.is_empty());
+
+		registry.register(&network("zombie-b", 2))?;
+		registry.register(&network("zombie-a", 1))?;
+		let networks = registry.networks()?;
+		assert_eq!(networks.len(), 2);
+		// Ordered by launch time
+		assert_eq!(networks[0], network("zombie-a", 1));
+		assert_eq!(networks[1], network("zombie-b", 2));
+		assert_eq!(registry.get("zombie-b")?, Some(network("zombie-b", 2)));
+
+		registry.remove("zombie-b")?;
+		assert_eq!(registry.get("zombie-b")?, None);
+		assert_eq!(registry.networks()?.len(), 1);
+		Ok(())
+	}
+
+	#[test]
+	fn test_registry_ignores_unknown_entries() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let registry = Registry::new(temp_dir.path());
+		create_dir_all(registry.path())?;
+		write(registry.path().join("network.log"), "")?;
+		write(registry.path().join("invalid.json"), "{}")?;
+		assert!(registry.networks()?.is_empty());
+		Ok(())
+	}
+
+	#[test]
+	fn test_registry_reads_entries_without_node_details() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let registry = Registry::new(temp_dir.path());
+		create_dir_all(registry.path())?;
+		write(
+			registry.path().join("zombie.json"),
+			r#"{"id":"zombie","base_dir":"/tmp/zombie","pid":1,"launched":0,
+			"relay_chain":{"chain":"rococo-local","para_id":null,
+			"nodes":[{"name":"alice","pid":null,"ws_uri":"ws://127.0.0.1:9944"}]},"parachains":[]}"#,
+		)?;
+		let network = registry.get("zombie")?.expect("network registered");
+		assert_eq!(network.relay_chain.nodes[0].prometheus_port, None);
+		Ok(())
+	}
+
+	#[test]
+	fn test_to_json() -> Result<()> {
+		let network = network("zombie", 0);
+		let json: serde_json::Value = serde_json::from_str(&network.to_json()?)?;
+		let node = &json["parachains"][0]["nodes"][0];
+		assert_eq!(json["parachains"][0]["para_id"], 1000);
+		assert_eq!(node["rpc_uri"], "http://127.0.0.1:9988");
+		assert_eq!(node["prometheus_port"], 9989);
+		assert_eq!(node["log_path"], "/tmp/zombie/asset-hub/asset-hub.log");
+		Ok(())
+	}
+
+	#[test]
+	fn test_prometheus_port() {
+		let args = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+		assert_eq!(prometheus_port(&args(&["--prometheus-port", "9615"])), Some(9615));
+		assert_eq!(prometheus_port(&args(&["--prometheus-port=9616", "--rpc-port"])), Some(9616));
+		assert_eq!(prometheus_port(&args(&["--no-prometheus"])), None);
+	}
+
+	#[test]
+	fn test_nodes() {
+		let network = network("zombie", 0);
+		let names: Vec<_> = network.nodes().map(|n| n.name.as_str()).collect();
+		assert_eq!(names, vec!["alice", "asset-hub"]);
+	}
+
+	#[test]
+	fn test_chain_label() {
+		let network = network("zombie", 0);
+		assert_eq!(network.relay_chain.label(), "rococo-local");
+		assert_eq!(network.parachains[0].label(), "asset-hub-rococo-local: 1000");
+		let chain = ChainInfo { chain: None, para_id: Some(2000), nodes: vec![] };
+		assert_eq!(chain.label(), "para_id: 2000");
+	}
+
+	#[test]
+	fn test_is_running() -> Result<()> {
+		let pid = std::process::id();
+		assert!(is_running(pid, started(pid).as_deref()));
+		assert!(!network("zombie", 0).is_running());
+		// A process without a recorded start time, or whose identifier has since been reused
+		assert!(!is_running(pid, None));
+		assert!(!is_running(pid, Some("Thu Jan  1 00:00:00 1970")));
+		// A process which has since exited
+		let mut child = std::process::Command::new("true").spawn()?;
+		child.wait()?;
+		assert!(!is_running(child.id(), None));
+		Ok(())
+	}
+
+	#[test]
+	fn test_node_pid() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let base_dir = temp_dir.path();
+		let log = base_dir.join("alice/alice.log");
+		create_dir_all(log.parent().expect("log within node directory"))?;
+		write(&log, "")?;
+		// A process following the node log is not mistaken for the node
+		let mut tail = std::process::Command::new("tail").arg("-f").arg(&log).spawn()?;
+		assert_eq!(node_pid(base_dir, "alice", "/bin/sh"), None);
+		// A stand-in for the node, with its data located within the network base directory
+		let mut node = std::process::Command::new("/bin/sh")
+			.args(["-c", "sleep 30; :"])
+			.arg(format!("{}/alice/", base_dir.display()))
+			.spawn()?;
+		sleep_until(|| node_pid(base_dir, "alice", "/bin/sh").is_some());
+		assert_eq!(node_pid(base_dir, "alice", "/bin/sh"), Some(node.id()));
+		assert_eq!(node_pid(base_dir, "alice", "polkadot"), None);
+		node.kill()?;
+		tail.kill()?;
+		Ok(())
+	}
+
+	// Waits briefly for a condition to be satisfied, as spawned processes may not yet be visible.
+	fn sleep_until(condition: impl Fn() -> bool) {
+		for _ in 0..20 {
+			if condition() {
+				return;
+			}
+			std::thread::sleep(Duration::from_millis(50));
+		}
+	}
+
+	#[test]
+	fn test_prune() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let registry = Registry::new(temp_dir.path());
+		let pid = std::process::id();
+		let running = NetworkInfo { pid, started: started(pid), ..network("zombie-a", 1) };
+		registry.register(&running)?;
+		// A stale entry, whose supervising process identifier may since have been reused
+		registry.register(&NetworkInfo { pid, ..network("zombie-b", 2) })?;
+		let removed = registry.prune()?;
+		assert_eq!(removed.iter().map(|n| n.id.as_str()).collect::<Vec<_>>(), ["zombie-b"]);
+		assert_eq!(registry.networks()?, vec![running]);
+		Ok(())
+	}
+}
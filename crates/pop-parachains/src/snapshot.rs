@@ -0,0 +1,167 @@
+// SPDX-License-Identifier: GPL-3.0
+use crate::errors::Error;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use std::{
+	fs::{create_dir_all, read_dir, remove_dir_all, remove_file, File},
+	path::Path,
+};
+use tar::{Archive, Builder};
+use tempfile::NamedTempFile;
+use walkdir::WalkDir;
+
+/// Creates a snapshot of a network, archiving the contents of its base directory (node databases,
+/// keystores and generated chain specifications) as a gzipped tarball. Node logs are excluded.
+///
+/// The network should be terminated beforehand, so that the databases of its nodes are consistent.
+///
+/// # Arguments
+///
+/// * `base_dir` - the base directory of the network
+/// * `archive` - the location of the resulting archive
+pub fn create_snapshot(base_dir: &Path, archive: &Path) -> Result<(), Error> {
+	if !base_dir.is_dir() {
+		return Err(Error::Archive(format!("{} is not a directory", base_dir.display())));
+	}
+	// Written to a temporary file alongside the archive, so that a failure does not leave a
+	// partial archive behind
+	let parent = archive.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+	create_dir_all(parent)?;
+	let file = NamedTempFile::new_in(parent)?;
+	let mut builder = Builder::new(GzEncoder::new(file.as_file(), Compression::default()));
+	builder.follow_symlinks(false);
+	for entry in WalkDir::new(base_dir).min_depth(1).sort_by_file_name() {
+		let entry = entry.map_err(|e| Error::Archive(e.to_string()))?;
+		let path = entry.path();
+		if entry.file_type().is_file() && path.extension().is_some_and(|ext| ext == "log") {
+			continue;
+		}
+		let name = path.strip_prefix(base_dir).expect("entry should be within base directory");
+		match entry.file_type().is_dir() {
+			true => builder.append_dir(name, path)?,
+			false => builder.append_path_with_name(path, name)?,
+		}
+	}
+	builder.into_inner()?.finish()?;
+	file.persist(archive).map_err(|e| Error::IO(e.error))?;
+	Ok(())
+}
+
+/// Restores a snapshot of a network, as created by [`create_snapshot`], into a base directory from
+/// which the network can then be launched.
+///
+/// # Arguments
+///
+/// * `archive` - the location of the snapshot
+/// * `base_dir` - the base directory of the network, which must either not exist or be empty
+pub fn restore_snapshot(archive: &Path, base_dir: &Path) -> Result<(), Error> {
+	restore_and_verify(archive, base_dir, || Ok(()))
+}
+
+/// Restores a snapshot of a network as per [`restore_snapshot`], then verifies the restored
+/// contents using the provided function. Should either fail, anything restored is removed so that
+/// the restore can be retried.
+///
+/// # Arguments
+///
+/// * `archive` - the location of the snapshot
+/// * `base_dir` - the base directory of the network, which must either not exist or be empty
+/// * `verify` - verifies the restored contents
+pub(crate) fn restore_and_verify(
+	archive: &Path,
+	base_dir: &Path,
+	verify: impl FnOnce() -> Result<(), Error>,
+) -> Result<(), Error> {
+	let existed = base_dir.exists();
+	if existed && read_dir(base_dir)?.next().is_some() {
+		return Err(Error::Archive(format!(
+			"cannot restore snapshot: {} is not empty",
+			base_dir.display()
+		)));
+	}
+	create_dir_all(base_dir)?;
+	let result = Archive::new(GzDecoder::new(File::open(archive)?))
+		.unpack(base_dir)
+		.map_err(|e| Error::Archive(format!("invalid snapshot {}: {e}", archive.display())))
+		.and_then(|_| verify());
+	if result.is_err() {
+		// Best effort, as the original error is of more use
+		let _ = match existed {
+			true => read_dir(base_dir).and_then(|entries| {
+				entries.flatten().try_for_each(|entry| match entry.file_type()?.is_dir() {
+					true => remove_dir_all(entry.path()),
+					false => remove_file(entry.path()),
+				})
+			}),
+			false => remove_dir_all(base_dir),
+		};
+	}
+	result
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+	use std::fs::{read_to_string, write};
+
+	#[test]
+	fn test_create_and_restore_snapshot() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let base_dir = temp_dir.path().join("zombie");
+		create_dir_all(base_dir.join("alice/data/chains/rococo_local_testnet/db"))?;
+		write(base_dir.join("alice/data/chains/rococo_local_testnet/db/CURRENT"), "MANIFEST-1")?;
+		write(base_dir.join("alice/alice.log"), "2024-04-04 10:00:00.000  INFO main")?;
+		write(base_dir.join("rococo-local.json"), "{}")?;
+		let archive = temp_dir.path().join("snapshots/network.tar.gz");
+
+		create_snapshot(&base_dir, &archive)?;
+		let restored = temp_dir.path().join("restored");
+		restore_snapshot(&archive, &restored)?;
+		assert_eq!(
+			read_to_string(restored.join("alice/data/chains/rococo_local_testnet/db/CURRENT"))?,
+			"MANIFEST-1"
+		);
+		assert_eq!(read_to_string(restored.join("rococo-local.json"))?, "{}");
+		// Logs are not included
+		assert!(restored.join("alice").is_dir());
+		assert!(!restored.join("alice/alice.log").exists());
+		Ok(())
+	}
+
+	#[test]
+	fn test_restore_snapshot_fails() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let base_dir = temp_dir.path().join("zombie");
+		create_dir_all(&base_dir)?;
+		write(base_dir.join("rococo-local.json"), "{}")?;
+		let archive = temp_dir.path().join("network.tar.gz");
+		create_snapshot(&base_dir, &archive)?;
+		// Existing data is never overwritten
+		assert!(matches!(
+			restore_snapshot(&archive, &base_dir),
+			Err(Error::Archive(message)) if message.ends_with("is not empty")
+		));
+		// Anything restored is removed should the restore fail
+		let restored = temp_dir.path().join("restored");
+		assert!(matches!(
+			restore_and_verify(&archive, &restored, || Err(Error::Config("mismatch".into()))),
+			Err(Error::Config(message)) if message == "mismatch"
+		));
+		assert!(!restored.exists());
+		create_dir_all(&restored)?;
+		assert!(
+			restore_and_verify(&archive, &restored, || Err(Error::Config(String::new()))).is_err()
+		);
+		assert_eq!(read_dir(&restored)?.count(), 0);
+		write(&archive, "not a snapshot")?;
+		assert!(matches!(
+			restore_snapshot(&archive, &restored),
+			Err(Error::Archive(message)) if message.starts_with("invalid snapshot")
+		));
+		assert!(matches!(
+			create_snapshot(&temp_dir.path().join("missing"), &archive),
+			Err(Error::Archive(_))
+		));
+		Ok(())
+	}
+}
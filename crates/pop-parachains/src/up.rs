@@ -1,24 +1,59 @@
 // SPDX-License-Identifier: GPL-3.0
+use crate::cache::Cache;
 use crate::errors::Error;
-use crate::utils::git::{Git, GitHub};
+use crate::fork::{self, Fork};
+use crate::snapshot::restore_and_verify;
+use crate::utils::git::{is_not_found, Git, GitHub, GitHubClient, Release};
+use crate::utils::image::ImageArchive;
 use duct::cmd;
 use indexmap::IndexMap;
+use reqwest::{
+	header::{CONTENT_TYPE, RANGE},
+	StatusCode,
+};
+use semver::{Version, VersionReq};
+use sha2::{Digest, Sha256};
 use std::{
 	env::current_dir,
-	fs::{copy, metadata, remove_dir_all, write, File},
+	fs::{
+		copy, create_dir_all, metadata, read_link, remove_dir_all, remove_file, rename, write,
+		File, OpenOptions,
+	},
 	io::{BufRead, Write},
 	os::unix::fs::PermissionsExt,
 	path::{Path, PathBuf},
+	time::{Duration, Instant},
 };
 use symlink::{remove_symlink_file, symlink_file};
 use tempfile::{Builder, NamedTempFile};
-use toml_edit::{value, DocumentMut, Formatted, Item, Table, Value};
+use tokio::time::sleep;
+use toml_edit::{value, ArrayOfTables, DocumentMut, Formatted, Item, Table, Value};
 use url::Url;
 use zombienet_sdk::{Network, NetworkConfig, NetworkConfigExt};
 use zombienet_support::fs::local::LocalFileSystem;
 
 const POLKADOT_SDK: &str = "https://github.com/paritytech/polkadot-sdk";
 const POLKADOT_DEFAULT_VERSION: &str = "v1.10.0";
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+/// The version used for local binaries linked into the cache.
+const LOCAL_VERSION: &str = "local";
+/// The first polkadot release including `polkadot-omni-node` and a `chain-spec-builder` able to
+/// generate parachain chain specifications.
+const OMNI_NODE_MIN_VERSION: &str = "v1.16.0";
+/// The preset used when generating the chain specification of a runtime-only parachain.
+const CHAIN_SPEC_PRESET: &str = "development";
+/// The system parachains which can be launched using `polkadot-parachain`.
+pub(crate) const SYSTEM_PARACHAINS: [SystemParachain; 9] = [
+	SystemParachain::new("asset-hub-rococo-local", 1_000, "v1.0.0"),
+	SystemParachain::new("asset-hub-westend-local", 1_000, "v1.0.0"),
+	SystemParachain::new("bridge-hub-rococo-local", 1_013, "v1.0.0"),
+	SystemParachain::new("bridge-hub-westend-local", 1_002, "v1.0.0"),
+	SystemParachain::new("collectives-westend-local", 1_001, "v1.0.0"),
+	SystemParachain::new("coretime-rococo-local", 1_005, "v1.7.0"),
+	SystemParachain::new("coretime-westend-local", 1_005, "v1.8.0"),
+	SystemParachain::new("people-rococo-local", 1_004, "v1.8.0"),
+	SystemParachain::new("people-westend-local", 1_004, "v1.8.0"),
+];
 
 pub struct Zombienet {
 	/// The cache location, used for caching binaries.
@@ -29,26 +64,95 @@ pub struct Zombienet {
 	relay_chain: Binary,
 	/// The binaries required to launch parachains.
 	parachains: IndexMap<u32, Binary>,
+	/// The runtimes of runtime-only parachains, which are launched using `polkadot-omni-node`.
+	runtimes: IndexMap<u32, PathBuf>,
+	/// The binary used to generate the chain specifications of runtime-only parachains, if any.
+	chain_spec_builder: Option<Binary>,
+	/// The generated chain specifications, retained while the network is in use.
+	chain_specs: Vec<NamedTempFile>,
+	/// The chain specifications of parachains forked from live chains.
+	forks: IndexMap<u32, PathBuf>,
+	/// The base directory of the network, when restored from a snapshot.
+	base_dir: Option<PathBuf>,
+	/// Any warnings encountered while resolving the binaries required.
+	warnings: Vec<String>,
 }
 
 impl Zombienet {
+	/// Initialises the configuration for launching a local network, resolving the binaries
+	/// required. When `offline`, versions are resolved purely from the cache and an error listing
+	/// any binaries which are not cached is returned.
+	///
+	/// # Arguments
+	///
+	/// * `cache` - the location of the cache, used for caching binaries
+	/// * `network_config` - the path to the network configuration file
+	/// * `relay_chain_version` - the version of the relay chain binary, as a release tag (e.g.
+	///   `v1.10.0`), a version requirement (e.g. `>=1.9, <1.11`), `latest` or `latest-rc`,
+	///   defaulting to the latest
+	/// * `system_parachain_version` - the version of the system parachain binary, defaulting to
+	///   that of the relay chain
+	/// * `parachains` - the parachains to be sourced, as per `pop up parachain --parachain`
+	/// * `offline` - whether to resolve binaries purely from the cache
 	pub async fn new(
 		cache: PathBuf,
 		network_config: &str,
 		relay_chain_version: Option<&String>,
 		system_parachain_version: Option<&String>,
 		parachains: Option<&Vec<String>>,
+		offline: bool,
+	) -> Result<Self, Error> {
+		let github = GitHubClient::new().with_cache(&cache);
+		Self::new_with(
+			cache,
+			network_config,
+			relay_chain_version,
+			system_parachain_version,
+			parachains,
+			offline,
+			&github,
+		)
+		.await
+	}
+
+	/// Initialises the configuration for launching a local network, as per [`Zombienet::new`],
+	/// using the provided client for any lookups of releases on GitHub.
+	///
+	/// # Arguments
+	///
+	/// * `cache` - the location of the cache, used for caching binaries
+	/// * `network_config` - the path to the network configuration file
+	/// * `relay_chain_version` - the version of the relay chain binary, as a release tag (e.g.
+	///   `v1.10.0`), a version requirement (e.g. `>=1.9, <1.11`), `latest` or `latest-rc`,
+	///   defaulting to the latest
+	/// * `system_parachain_version` - the version of the system parachain binary, defaulting to
+	///   that of the relay chain
+	/// * `parachains` - the parachains to be sourced, as per `pop up parachain --parachain`
+	/// * `offline` - whether to resolve binaries purely from the cache
+	/// * `github` - the client used to look up releases on GitHub
+	pub async fn new_with(
+		cache: PathBuf,
+		network_config: &str,
+		relay_chain_version: Option<&String>,
+		system_parachain_version: Option<&String>,
+		parachains: Option<&Vec<String>>,
+		offline: bool,
+		github: &GitHubClient,
 	) -> Result<Self, Error> {
 		// Parse network config
 		let network_config_path = PathBuf::from(network_config);
-		let config = std::fs::read_to_string(&network_config_path)
+		let mut config = std::fs::read_to_string(&network_config_path)
 			.map_err(|err| Error::IO(err))
-			.and_then(|content| {
-				content.parse::<DocumentMut>().map_err(|err| Error::TomlError(err.into()))
-			})?;
+			.and_then(|content| parse_config(&network_config_path, &content))?;
+		SystemParachain::assign_para_ids(&mut config);
 		// Determine binaries
-		let relay_chain_binary = Self::relay_chain(relay_chain_version, &config, &cache).await?;
+		let mut warnings = Vec::new();
+		let relay_chain_binary =
+			Self::relay_chain(relay_chain_version, &config, &cache, offline, github, &mut warnings)
+				.await?;
 		let mut parachain_binaries = IndexMap::new();
+		let mut runtimes = IndexMap::new();
+		let mut chain_spec_builder = None;
 		if let Some(tables) = config.get("parachains").and_then(|p| p.as_array_of_tables()) {
 			for table in tables.iter() {
 				let id = table
@@ -85,34 +189,253 @@ impl Zombienet {
 				};
 				let command = command.value().to_lowercase();
 				if command == "polkadot-parachain" {
+					let version = system_parachain_version.unwrap_or(&relay_chain_binary.version);
+					if let Some(chain) = table.get("chain").and_then(|c| c.as_str()) {
+						SystemParachain::verify(chain, version, &mut warnings)?;
+					}
 					parachain_binaries.insert(
 						id,
-						Self::system_parachain(
-							system_parachain_version.unwrap_or(&relay_chain_binary.version),
-							&cache,
-						)?,
+						Self::system_parachain(version, &cache, github, &mut warnings).await?,
 					);
 				} else if let Some(parachains) = parachains {
 					for parachain in parachains {
-						let url = Url::parse(parachain).map_err(|err| Error::from(err))?;
-						let name = match GitHub::name(&url) {
-							Ok(name) => name,
-							Err(err) => return Err(Error::from(err)),
-						};
-						if command == name {
-							parachain_binaries.insert(id, Self::parachain(url, &cache)?);
+						let (para_id, source) = parse_parachain(parachain);
+						if para_id.is_some_and(|para_id| para_id != id) {
+							continue;
+						}
+						let source = ParachainSource::parse(source)?;
+						// Match the command by name, or by file name when a path
+						let name = source.name()?.to_lowercase();
+						if command != name &&
+							!Path::new(&command).file_name().is_some_and(|f| *f == *name)
+						{
+							continue;
 						}
+						let binary = match source {
+							ParachainSource::Git(url) => Self::parachain(url, &cache, offline)?,
+							ParachainSource::Local(path) => Self::local(&path, &cache)?,
+							ParachainSource::Archive { archive, binary, version } =>
+								Self::archive(&archive, &binary, version.as_deref(), &cache)?,
+							ParachainSource::Template { template, name, version } =>
+								Self::template(&template, &name, version.as_deref(), &cache)?,
+							ParachainSource::Runtime(runtime) => {
+								let version = Self::omni_node_version(
+									system_parachain_version,
+									&relay_chain_binary.version,
+								);
+								runtimes.insert(id, runtime.canonicalize()?);
+								if chain_spec_builder.is_none() {
+									chain_spec_builder = Some(
+										Self::release_binary(
+											"chain-spec-builder",
+											"staging-chain-spec-builder",
+											&version,
+											&cache,
+											github,
+											&mut warnings,
+										)
+										.await?,
+									);
+								}
+								Self::release_binary(
+									"polkadot-omni-node",
+									"polkadot-omni-node",
+									&version,
+									&cache,
+									github,
+									&mut warnings,
+								)
+								.await?
+							},
+						};
+						parachain_binaries.insert(id, binary);
 					}
 				}
 			}
 		}
 
-		Ok(Self {
+		let zombienet = Self {
 			cache,
 			network_config: (network_config_path, config),
 			relay_chain: relay_chain_binary,
 			parachains: parachain_binaries,
-		})
+			runtimes,
+			chain_spec_builder,
+			chain_specs: Vec::new(),
+			forks: IndexMap::new(),
+			base_dir: None,
+			warnings,
+		};
+		if offline {
+			zombienet.ensure_cached()?;
+		}
+		Ok(zombienet)
+	}
+
+	// Ensures that all required binaries are available within the cache, as none can be sourced
+	// while offline.
+	fn ensure_cached(&self) -> Result<(), Error> {
+		let mut missing: Vec<_> = self
+			.missing_binaries()
+			.iter()
+			// Local binaries can be sourced while offline
+			.filter(|b| b.sources.is_empty() || !b.sources.iter().all(Source::is_local))
+			.map(|b| b.name.clone())
+			.collect();
+		let version = Platform::HOST.cached_version(&self.relay_chain.version);
+		for worker in ["polkadot-execute-worker", "polkadot-prepare-worker"] {
+			let worker = Source::versioned_name(worker, Some(&version));
+			if !self.cache.join(&worker).exists() && !missing.contains(&self.relay_chain.name) {
+				missing.push(worker);
+			}
+		}
+		if !missing.is_empty() {
+			return Err(Error::Offline(format!(
+				"the following binaries are required but not cached: {}. Run without `--offline` \
+				to source them",
+				missing.join(", ")
+			)));
+		}
+		Ok(())
+	}
+
+	/// The version of the relay chain binary to be used.
+	pub fn relay_chain_version(&self) -> &str {
+		&self.relay_chain.version
+	}
+
+	/// Any warnings encountered while resolving the binaries required, such as a failure to look
+	/// up the latest relay chain release.
+	pub fn warnings(&self) -> &[String] {
+		&self.warnings
+	}
+
+	/// Restores the state of the network from a snapshot, as created by [`create_snapshot`], so
+	/// that its nodes resume from the saved state rather than from genesis. The snapshot is
+	/// extracted into the base directory specified by the network configuration, which must be
+	/// empty, or otherwise into a new directory. Returns the base directory of the network.
+	///
+	/// # Arguments
+	///
+	/// * `snapshot` - the location of the snapshot
+	///
+	/// [`create_snapshot`]: crate::create_snapshot
+	pub fn restore(&mut self, snapshot: &Path) -> Result<PathBuf, Error> {
+		let network_config = &self.network_config.1;
+		let base_dir = match network_config
+			.get("settings")
+			.and_then(|s| s.get("base_dir"))
+			.and_then(|d| d.as_str())
+		{
+			Some(base_dir) => PathBuf::from(base_dir),
+			None => Builder::new().prefix("zombie-").tempdir()?.into_path(),
+		};
+		// Nodes without any saved state would otherwise silently start from genesis
+		restore_and_verify(snapshot, &base_dir, || {
+			let missing: Vec<_> =
+				node_names(network_config).filter(|n| !base_dir.join(n).is_dir()).collect();
+			match missing.is_empty() {
+				true => Ok(()),
+				false => Err(Error::Config(format!(
+					"the snapshot does not match the network configuration, no state found for: {}",
+					missing.join(", ")
+				))),
+			}
+		})?;
+		self.base_dir = Some(base_dir.clone());
+		Ok(base_dir)
+	}
+
+	/// Forks the state of a live chain into a parachain of the network, so that the parachain is
+	/// launched from the state of the live chain rather than from genesis. A local raw chain
+	/// specification is generated using the parachain binary, which must therefore be available,
+	/// with its state then replaced by that downloaded from the live chain. Returns the identifier
+	/// of the forked parachain.
+	///
+	/// # Arguments
+	///
+	/// * `fork` - the live chain to be forked, as `[id=]<ws-uri>[@block]` (e.g.
+	///   `2000=wss://rpc.example.com@1000`). The parachain identifier can be omitted when the
+	///   network includes a single parachain.
+	/// * `status` - used to observe status updates
+	pub async fn fork(&mut self, fork: &str, status: impl Status) -> Result<u32, Error> {
+		let (id, fork) = parse_parachain(fork);
+		let fork = Fork::parse(fork)?;
+		let (network_config_path, network_config) = &self.network_config;
+		let tables: Vec<_> = network_config
+			.get("parachains")
+			.and_then(|p| p.as_array_of_tables())
+			.into_iter()
+			.flat_map(|p| p.iter())
+			.collect();
+		let table = match id {
+			Some(id) => tables
+				.into_iter()
+				.find(|t| t.get("id").and_then(|i| i.as_integer()) == Some(id as i64))
+				.ok_or(Error::Config(format!("cannot fork parachain {id}: not found")))?,
+			None if tables.len() == 1 => tables[0],
+			None =>
+				return Err(Error::Config(
+					"the identifier of the parachain to be forked must be specified".into(),
+				)),
+		};
+		let id = table
+			.get("id")
+			.and_then(|i| i.as_integer())
+			.ok_or(Error::Config("expected `parachain` to have `id`".into()))? as u32;
+		if self.runtimes.contains_key(&id) {
+			return Err(Error::Config(format!(
+				"cannot fork parachain {id}: runtime-only parachains cannot be forked"
+			)));
+		}
+		let binary = match self.parachains.get(&id) {
+			Some(binary) => binary.path.clone(),
+			None => {
+				// Fall back to the first command specified by a collator, as when sourcing binaries
+				let command = table
+					.get("default_command")
+					.and_then(|c| c.as_str())
+					.or_else(|| {
+						table
+							.get("collators")
+							.and_then(|c| c.as_array_of_tables())?
+							.iter()
+							.find_map(|c| c.get("command").and_then(|c| c.as_str()))
+					})
+					.ok_or(Error::MissingBinary(format!("the binary of parachain {id}")))?;
+				Self::resolve_path(&mut network_config_path.clone(), command)?
+			},
+		};
+		let relay_chain = network_config
+			.get("relaychain")
+			.and_then(|r| r.get("chain"))
+			.and_then(|c| c.as_str())
+			.ok_or(Error::Config("expected `relaychain` to have `chain`".into()))?;
+
+		// Generate a local chain specification, whose state is then replaced by the forked state
+		let mut args = vec!["build-spec", "--raw", "--disable-default-bootnode"];
+		if let Some(chain) = table.get("chain").and_then(|c| c.as_str()) {
+			args.extend(["--chain", chain]);
+		}
+		let output = cmd(&binary, args).stdout_capture().stderr_capture().unchecked().run()?;
+		if !output.status.success() {
+			return Err(Error::ChainSpec(format!(
+				"unable to generate the chain specification of parachain {id} using {}: {}",
+				binary.display(),
+				String::from_utf8_lossy(&output.stderr).trim()
+			)));
+		}
+		let chain_spec = serde_json::from_slice(&output.stdout).map_err(|e| {
+			Error::ChainSpec(format!("invalid chain specification of parachain {id}: {e}"))
+		})?;
+		let state = fork.state(status).await?;
+		let chain_spec = fork::chain_spec(chain_spec, state, id, relay_chain)?;
+
+		let file = Builder::new().prefix("chain-spec-").suffix(".json").tempfile()?;
+		write(file.path(), chain_spec.to_string())?;
+		self.forks.insert(id, file.path().to_path_buf());
+		self.chain_specs.push(file);
+		Ok(id)
 	}
 
 	pub fn missing_binaries(&self) -> Vec<&Binary> {
@@ -120,20 +443,31 @@ impl Zombienet {
 		if !self.relay_chain.path.exists() {
 			missing.push(&self.relay_chain);
 		}
-		for binary in self.parachains.values().filter(|b| !b.path.exists()) {
-			missing.push(binary);
+		for binary in self.parachains.values().chain(&self.chain_spec_builder) {
+			if !binary.path.exists() {
+				missing.push(binary);
+			}
 		}
 		missing
 	}
 
 	pub async fn spawn(&mut self) -> Result<Network<LocalFileSystem>, Error> {
 		// Symlink polkadot-related binaries
+		let version = Platform::HOST.cached_version(&self.relay_chain.version);
 		for file in ["polkadot-execute-worker", "polkadot-prepare-worker"] {
 			let dest = self.cache.join(file);
 			if dest.exists() {
 				remove_symlink_file(&dest)?;
 			}
-			symlink_file(self.cache.join(format!("{file}-{}", self.relay_chain.version)), dest)?;
+			symlink_file(self.cache.join(Source::versioned_name(file, Some(&version))), dest)?;
+		}
+
+		// Record usage of cached binaries
+		let cache = Cache::new(&self.cache);
+		for path in
+			std::iter::once(&self.relay_chain.path).chain(self.parachains.values().map(|b| &b.path))
+		{
+			cache.touch(path)?;
 		}
 
 		// Load from config and spawn network
@@ -159,6 +493,12 @@ impl Zombienet {
 		settings
 			.entry("node_spawn_timeout")
 			.or_insert(Item::Value(Value::Integer(Formatted::new(300))));
+		if let Some(base_dir) = &self.base_dir {
+			let base_dir = base_dir
+				.to_str()
+				.ok_or(Error::Config("the base directory path is invalid".into()))?;
+			settings.insert("base_dir", value(base_dir));
+		}
 
 		// Update relay chain config
 		let relay_path = self
@@ -172,6 +512,7 @@ impl Zombienet {
 			return Err(Error::Config("expected `relaychain`".into()));
 		};
 		*relay_chain.entry("default_command").or_insert(value(relay_path)) = value(relay_path);
+		let relay_chain = relay_chain.get("chain").and_then(|c| c.as_str()).map(String::from);
 
 		// Update parachain config
 		if let Some(tables) =
@@ -183,6 +524,33 @@ impl Zombienet {
 					.and_then(|i| i.as_integer())
 					.ok_or(Error::Config("expected `parachain` to have `id`".into()))? as u32;
 
+				// Generate the chain specification of a runtime-only parachain
+				if let Some(runtime) = self.runtimes.get(&id) {
+					let chain_spec_builder = self
+						.chain_spec_builder
+						.as_ref()
+						.ok_or(Error::MissingBinary("chain-spec-builder".into()))?;
+					let relay_chain = relay_chain
+						.as_deref()
+						.ok_or(Error::Config("expected `relaychain` to have `chain`".into()))?;
+					let chain_spec =
+						Self::generate_chain_spec(chain_spec_builder, runtime, relay_chain, id)?;
+					let path = chain_spec
+						.path()
+						.to_str()
+						.ok_or(Error::Config("the chain specification path is invalid".into()))?;
+					table.insert("chain_spec_path", value(path));
+					self.chain_specs.push(chain_spec);
+				}
+
+				// Launch a forked parachain from the state of the live chain
+				if let Some(chain_spec) = self.forks.get(&id) {
+					let path = chain_spec
+						.to_str()
+						.ok_or(Error::Config("the chain specification path is invalid".into()))?;
+					table.insert("chain_spec_path", value(path));
+				}
+
 				// Resolve default_command to binary
 				{
 					// Check if provided via args, therefore cached
@@ -267,6 +635,9 @@ impl Zombienet {
 		version: Option<&String>,
 		network_config: &DocumentMut,
 		cache: &PathBuf,
+		offline: bool,
+		github: &GitHubClient,
+		warnings: &mut Vec<String>,
 	) -> Result<Binary, Error> {
 		const BINARY: &str = "polkadot";
 		let relay_command = network_config
@@ -281,213 +652,1352 @@ impl Zombienet {
 				)));
 			}
 		}
-		let version = match version {
-			Some(v) => v.to_string(),
-			None => Self::latest_polkadot_release().await?,
+		let version = match RelayChainVersion::parse(version.map(|v| v.as_str()))? {
+			RelayChainVersion::Exact(version) => version,
+			selection if offline => Self::latest_cached_release(cache, &selection)?,
+			selection => match Self::latest_polkadot_release(github, &selection).await {
+				Ok(Some(version)) => version,
+				Ok(None) if selection == RelayChainVersion::Latest => {
+					warnings.push(format!(
+						"no polkadot release could be identified, so {POLKADOT_DEFAULT_VERSION} is \
+						used instead"
+					));
+					POLKADOT_DEFAULT_VERSION.to_string()
+				},
+				Ok(None) =>
+					return Err(Error::Config(format!(
+						"no polkadot release satisfies `{selection}`"
+					))),
+				// Fall back to a cached release, or the default when the latest is required
+				Err(e) => {
+					let fallback =
+						Self::latest_cached_release(cache, &selection).ok().or_else(|| {
+							(selection == RelayChainVersion::Latest)
+								.then(|| POLKADOT_DEFAULT_VERSION.to_string())
+						});
+					let Some(version) = fallback else {
+						return Err(e);
+					};
+					warnings.push(format!(
+						"unable to look up polkadot releases ({e}), so {version} is used instead"
+					));
+					version
+				},
+			},
 		};
-		let versioned_name = format!("{BINARY}-{version}");
+		let versioned_name =
+			Source::versioned_name(BINARY, Some(&Platform::HOST.cached_version(&version)));
 		let path = cache.join(&versioned_name);
 		let mut sources = Vec::new();
 		if !path.exists() {
 			const BINARIES: [&str; 3] =
 				[BINARY, "polkadot-execute-worker", "polkadot-prepare-worker"];
-			let repo = Url::parse(POLKADOT_SDK).expect("repository url valid");
-			if cfg!(target_os = "macos") {
-				sources.push(Source::Git {
-					url: repo.into(),
-					branch: Some(format!("release-polkadot-{version}")),
-					package: BINARY.into(),
-					binaries: BINARIES.iter().map(|b| b.to_string()).collect(),
-					version: Some(version.clone()),
-				});
-			} else {
-				for b in BINARIES {
-					sources.push(Source::Url {
-						name: b.to_string(),
-						version: version.clone(),
-						url: GitHub::release(&repo, &format!("polkadot-{version}"), b),
-					})
-				}
-			};
+			sources = Self::release_sources(
+				Platform::HOST,
+				github,
+				&version,
+				BINARY,
+				&BINARIES,
+				warnings,
+			)
+			.await;
 		}
 
 		Ok(Binary { name: versioned_name, version, path, sources })
 	}
 
-	fn system_parachain(version: &String, cache: &PathBuf) -> Result<Binary, Error> {
-		const BINARY: &str = "polkadot-parachain";
-		let versioned_name = format!("{BINARY}-{version}");
+	async fn system_parachain(
+		version: &String,
+		cache: &PathBuf,
+		github: &GitHubClient,
+		warnings: &mut Vec<String>,
+	) -> Result<Binary, Error> {
+		Self::release_binary(
+			"polkadot-parachain",
+			"polkadot-parachain-bin",
+			version,
+			cache,
+			github,
+			warnings,
+		)
+		.await
+	}
+
+	// Resolves a binary of a polkadot-sdk release.
+	async fn release_binary(
+		binary: &str,
+		package: &str,
+		version: &str,
+		cache: &Path,
+		github: &GitHubClient,
+		warnings: &mut Vec<String>,
+	) -> Result<Binary, Error> {
+		let versioned_name =
+			Source::versioned_name(binary, Some(&Platform::HOST.cached_version(version)));
 		let path = cache.join(&versioned_name);
 		let mut sources = Vec::new();
 		if !path.exists() {
-			let repo = Url::parse(POLKADOT_SDK).expect("repository url valid");
-			if cfg!(target_os = "macos") {
-				sources.push(Source::Git {
-					url: repo.into(),
-					branch: Some(format!("release-polkadot-{version}")),
-					package: "polkadot-parachain-bin".into(),
-					binaries: vec![BINARY.into()],
-					version: Some(version.into()),
-				})
-			} else {
-				sources.push(Source::Url {
-					name: BINARY.into(),
-					version: version.into(),
-					url: GitHub::release(&repo, &format!("polkadot-{version}"), BINARY),
-				})
-			};
+			sources = Self::release_sources(
+				Platform::HOST,
+				github,
+				version,
+				package,
+				&[binary],
+				warnings,
+			)
+			.await;
 		}
 		Ok(Binary { name: versioned_name, version: version.into(), path, sources })
 	}
 
-	fn parachain(repo: Url, cache: &PathBuf) -> Result<Binary, Error> {
+	// The version of `polkadot-omni-node` and `chain-spec-builder` to be used: that of the system
+	// parachains when specified, otherwise that of the relay chain, provided it includes them.
+	fn omni_node_version(system_parachain_version: Option<&String>, relay_chain: &str) -> String {
+		if let Some(version) = system_parachain_version {
+			return version.clone();
+		}
+		let min = release_version(OMNI_NODE_MIN_VERSION).expect("valid version");
+		match release_version(relay_chain) {
+			Some(version) if version < min => OMNI_NODE_MIN_VERSION.into(),
+			// Versions which cannot be compared (e.g. `stable2409`) are assumed to include them
+			_ => relay_chain.into(),
+		}
+	}
+
+	// Generates the chain specification of a runtime-only parachain using `chain-spec-builder`.
+	fn generate_chain_spec(
+		chain_spec_builder: &Binary,
+		runtime: &Path,
+		relay_chain: &str,
+		id: u32,
+	) -> Result<NamedTempFile, Error> {
+		let chain_spec = Builder::new().prefix("chain-spec-").suffix(".json").tempfile()?;
+		let output = cmd(
+			&chain_spec_builder.path,
+			[
+				"-c".into(),
+				chain_spec.path().as_os_str().to_owned(),
+				"create".into(),
+				"--relay-chain".into(),
+				relay_chain.into(),
+				"--para-id".into(),
+				id.to_string().into(),
+				"-r".into(),
+				runtime.as_os_str().to_owned(),
+				"named-preset".into(),
+				CHAIN_SPEC_PRESET.into(),
+			],
+		)
+		.stderr_to_stdout()
+		.stdout_capture()
+		.unchecked()
+		.run()?;
+		if !output.status.success() {
+			return Err(Error::ChainSpec(format!(
+				"unable to generate the chain specification of parachain {id} from {}: {}",
+				runtime.display(),
+				String::from_utf8_lossy(&output.stdout).trim()
+			)));
+		}
+		Ok(chain_spec)
+	}
+
+	// Determines the sources of binaries of a polkadot-sdk release for a platform: the release
+	// artifacts when published for the platform, otherwise a build of the release branch. A
+	// warning is recorded when the release cannot be looked up, as the build is lengthy.
+	async fn release_sources(
+		platform: Platform,
+		github: &GitHubClient,
+		version: &str,
+		package: &str,
+		binaries: &[&str],
+		warnings: &mut Vec<String>,
+	) -> Vec<Source> {
+		let repo = Url::parse(POLKADOT_SDK).expect("repository url valid");
+		let tag = format!("polkadot-{version}");
+		let cached_version = platform.cached_version(version);
+		let artifacts: Option<Vec<_>> = binaries.iter().map(|b| platform.artifact(b)).collect();
+		let published = match &artifacts {
+			Some(_) if platform.is_default() => true,
+			// Artifacts for other platforms are only published for more recent releases
+			Some(artifacts) => match github.get_release(&repo, &tag).await {
+				Ok(release) => artifacts
+					.iter()
+					.all(|a| release.assets.iter().any(|asset| asset.name == *a)),
+				Err(e) if is_not_found(&e) => false,
+				Err(e) => {
+					warnings.push(format!(
+						"unable to look up the {tag} release ({e}), so {package} is built from \
+						source instead"
+					));
+					false
+				},
+			},
+			None => false,
+		};
+		match artifacts.filter(|_| published) {
+			Some(artifacts) => binaries
+				.iter()
+				.zip(artifacts)
+				.map(|(binary, artifact)| Source::Url {
+					name: binary.to_string(),
+					version: cached_version.clone(),
+					url: GitHub::release(&repo, &tag, &artifact),
+					digest: None,
+				})
+				.collect(),
+			None => vec![Source::Git {
+				url: repo.into(),
+				branch: Some(format!("release-polkadot-{version}")),
+				package: package.into(),
+				binaries: binaries.iter().map(|b| b.to_string()).collect(),
+				version: Some(cached_version),
+			}],
+		}
+	}
+
+	fn parachain(repo: Url, cache: &Path, offline: bool) -> Result<Binary, Error> {
 		let binary = repo.query();
-		let branch = repo.fragment().map(|f| f.to_string());
+		let reference = repo.fragment();
 		let mut url = repo.clone();
 		url.set_query(None);
 		url.set_fragment(None);
 		let binary = match binary {
 			Some(b) => b,
-			None => GitHub::name(&url)?,
+			None => Git::provider(&url).name(&url)?,
 		}
 		.to_string();
 
-		let path = cache.join(&binary);
+		// Pin the binary to the commit currently referenced by the branch/tag
+		let commit = match reference {
+			Some(commit) if offline && Git::is_commit(commit) => commit.to_lowercase(),
+			_ if offline => return Self::latest_cached_build(&url, &binary, cache),
+			_ => Git::resolve_commit(&url, reference)?,
+		};
+		let version = commit[..7].to_string();
+		let versioned_name = Source::versioned_name(&binary, Some(&version));
+		let path = cache.join(&versioned_name);
 		let mut sources = Vec::new();
 		if !path.exists() {
 			sources.push(Source::Git {
-				url: repo.clone(),
-				branch: branch.clone(),
+				url,
+				branch: Some(commit),
 				package: binary.clone(),
-				binaries: vec![binary.clone()],
-				version: branch,
+				binaries: vec![binary],
+				version: Some(version.clone()),
+			})
+		}
+		Ok(Binary { name: versioned_name, version, path, sources })
+	}
+
+	// Resolves the most recently used build of a binary from a git repository within the cache, as
+	// the commit referenced by a branch/tag cannot be resolved while offline.
+	fn latest_cached_build(url: &Url, binary: &str, cache: &Path) -> Result<Binary, Error> {
+		Cache::new(cache)
+			.binaries()?
+			.into_iter()
+			.filter(|b| {
+				b.name == binary &&
+					b.source.as_ref().is_some_and(|s| s.split('#').next() == Some(url.as_str()))
+			})
+			.max_by_key(|b| b.last_used)
+			.map(|b| {
+				let version = b.version.unwrap_or_default();
+				let name = Source::versioned_name(binary, Some(&version));
+				Binary { name, version, path: b.path, sources: Vec::new() }
+			})
+			.ok_or(Error::Offline(format!(
+				"no build of {binary} from {url} is cached. Run without `--offline` to source it"
+			)))
+	}
+
+	// Resolves a local binary, which is linked into the cache.
+	fn local(path: &Path, cache: &Path) -> Result<Binary, Error> {
+		let name = file_name(path)?;
+		let versioned_name = Source::versioned_name(&name, Some(LOCAL_VERSION));
+		let link = cache.join(&versioned_name);
+		// Remove any existing link to another binary, so that it is linked afresh
+		let path = path.canonicalize()?;
+		if link.is_symlink() && read_link(&link)? != path {
+			remove_symlink_file(&link)?;
+		}
+		let mut sources = Vec::new();
+		if !link.exists() {
+			sources.push(Source::Local { name, path })
+		}
+		Ok(Binary { name: versioned_name, version: LOCAL_VERSION.into(), path: link, sources })
+	}
+
+	// Resolves a binary within a local OCI/Docker image archive.
+	fn archive(
+		archive: &Path,
+		binary: &str,
+		version: Option<&str>,
+		cache: &Path,
+	) -> Result<Binary, Error> {
+		let name = file_name(Path::new(binary))?;
+		let versioned_name = Source::versioned_name(&name, version);
+		let path = cache.join(&versioned_name);
+		let mut sources = Vec::new();
+		if !path.exists() {
+			sources.push(Source::Archive {
+				name,
+				version: version.map(|v| v.into()),
+				archive: archive.canonicalize()?,
+				binary: binary.into(),
+			})
+		}
+		Ok(Binary {
+			name: versioned_name,
+			version: version.unwrap_or_default().into(),
+			path,
+			sources,
+		})
+	}
+
+	// Resolves a binary downloaded from a url template, substituting any `{version}` and `{arch}`
+	// placeholders.
+	fn template(
+		template: &str,
+		name: &str,
+		version: Option<&str>,
+		cache: &Path,
+	) -> Result<Binary, Error> {
+		if template.contains("{version}") && version.is_none() {
+			return Err(Error::Config(format!(
+				"a version is required by the url template, specified as #fragment: {template}"
+			)));
+		}
+		let url = template
+			.replace("{version}", version.unwrap_or_default())
+			.replace("{arch}", std::env::consts::ARCH);
+		let versioned_name = Source::versioned_name(name, version);
+		let path = cache.join(&versioned_name);
+		let mut sources = Vec::new();
+		if !path.exists() {
+			sources.push(Source::Url {
+				name: name.into(),
+				version: version.unwrap_or_default().into(),
+				url,
+				digest: None,
 			})
 		}
-		Ok(Binary { name: binary, version: "".into(), path, sources })
+		Ok(Binary {
+			name: versioned_name,
+			version: version.unwrap_or_default().into(),
+			path,
+			sources,
+		})
+	}
+
+	// Resolves the version of the newest polkadot binary within the cache satisfying the
+	// selection.
+	fn latest_cached_release(cache: &Path, selection: &RelayChainVersion) -> Result<String, Error> {
+		Cache::new(cache)
+			.binaries()?
+			.into_iter()
+			.filter(|b| b.name == "polkadot")
+			.filter_map(|b| b.version)
+			// Only binaries built for the host are considered
+			.filter_map(|v| Platform::HOST.version(&v).map(String::from))
+			.find(|v| release_version(v).is_some_and(|v| selection.matches(&v)))
+			.ok_or_else(|| {
+				let requirement = match selection {
+					RelayChainVersion::Latest => String::new(),
+					selection => format!(" satisfying `{selection}`"),
+				};
+				Error::Offline(format!(
+					"no polkadot binary{requirement} is cached, so the relay chain version cannot be \
+					resolved. Run without `--offline` to source it"
+				))
+			})
 	}
 
-	async fn latest_polkadot_release() -> Result<String, Error> {
+	// Resolves the version of the newest polkadot release satisfying the selection, if any, looking
+	// up pages of releases until one does. Releases whose tags are not semantic versions (e.g.
+	// `polkadot-stable2409`) cannot be ordered, so are excluded; these can only be used when
+	// specified exactly.
+	async fn latest_polkadot_release(
+		github: &GitHubClient,
+		selection: &RelayChainVersion,
+	) -> Result<Option<String>, Error> {
 		let repo = Url::parse(POLKADOT_SDK).expect("repository url valid");
-		// Fetching latest releases
-		for release in GitHub::get_latest_releases(&repo).await? {
-			if !release.prerelease && release.tag_name.starts_with("polkadot-v") {
-				return Ok(release
-					.tag_name
-					.strip_prefix("polkadot-")
-					.map_or_else(|| release.tag_name.clone(), |v| v.to_string()));
-			}
+		let versions = |releases: &[Release]| -> Vec<(Version, String)> {
+			releases
+				.iter()
+				.filter_map(|r| {
+					let tag = r.tag_name.strip_prefix("polkadot-")?;
+					Some((release_version(tag)?, tag.to_string()))
+				})
+				.filter(|(v, _)| selection.matches(v))
+				.collect()
+		};
+		let releases =
+			github.get_releases_until(&repo, |releases| !versions(releases).is_empty()).await?;
+		Ok(versions(&releases).into_iter().max_by(|(a, _), (b, _)| a.cmp(b)).map(|(_, tag)| tag))
+	}
+}
+
+/// The operating system and architecture on which binaries are run, determining which release
+/// artifacts can be used.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Platform {
+	os: &'static str,
+	arch: &'static str,
+}
+
+impl Platform {
+	/// The platform of the host.
+	const HOST: Self = Self { os: std::env::consts::OS, arch: std::env::consts::ARCH };
+	/// The architecture of cached binaries whose names do not include an architecture.
+	const DEFAULT_ARCH: &'static str = "x86_64";
+
+	// Whether release artifacts are published for the platform for every release.
+	fn is_default(&self) -> bool {
+		self.os == "linux" && self.arch == Self::DEFAULT_ARCH
+	}
+
+	// The name of the release artifact of a binary built for the platform, if any are published.
+	fn artifact(&self, binary: &str) -> Option<String> {
+		match (self.os, self.arch) {
+			("linux", "x86_64") => Some(binary.into()),
+			("macos", "aarch64") => Some(format!("{binary}-aarch64-apple-darwin")),
+			_ => None,
+		}
+	}
+
+	// The version under which a binary built for the platform is cached, with any architecture
+	// other than the default appended as build metadata (e.g. `v1.10.0+aarch64`).
+	fn cached_version(&self, version: &str) -> String {
+		match self.arch {
+			Self::DEFAULT_ARCH => version.into(),
+			arch => format!("{version}+{arch}"),
+		}
+	}
+
+	// The version of a cached binary, provided it was built for the platform.
+	fn version<'a>(&self, cached_version: &'a str) -> Option<&'a str> {
+		match cached_version.split_once('+') {
+			Some((version, arch)) => (arch == self.arch).then_some(version),
+			None => (self.arch == Self::DEFAULT_ARCH).then_some(cached_version),
 		}
-		// It should never reach this point, but in case we download a default version of polkadot
-		Ok(POLKADOT_DEFAULT_VERSION.to_string())
 	}
 }
 
-/// A binary used to launch a node.
-pub struct Binary {
-	/// The name of a binary.
-	pub name: String,
-	version: String,
-	path: PathBuf,
-	sources: Vec<Source>,
+/// The version of the relay chain to be used.
+#[derive(Clone, Debug, PartialEq)]
+enum RelayChainVersion {
+	/// The latest release, excluding release candidates.
+	Latest,
+	/// The latest release, including release candidates.
+	LatestRc,
+	/// A specific release (e.g. `v1.10.0` or `stable2409`).
+	Exact(String),
+	/// The latest release satisfying a requirement (e.g. `>=1.9, <1.11`).
+	Requirement(VersionReq),
 }
 
-impl Binary {
-	/// Sources the binary by either downloading from a url or by cloning a git repository and
-	/// building locally from the resulting source code.
-	///
-	/// # Arguments
-	///
-	/// * `cache` - path to the local cache
-	/// * `status` - used to observe status updates
-	pub async fn source(&self, cache: &PathBuf, status: impl Status) -> Result<(), Error> {
-		for source in &self.sources {
-			source.process(cache, status).await?;
+impl RelayChainVersion {
+	// Parses the version specified. A version is considered a requirement when it starts with a
+	// comparison operator or a number (e.g. `>=1.9, <1.11` or `1.9`), with any other version,
+	// including full versions and tags such as `stable2409`, considered a specific release.
+	fn parse(version: Option<&str>) -> Result<Self, Error> {
+		Ok(match version.map(str::trim) {
+			None | Some("latest") => Self::Latest,
+			Some("latest-rc") => Self::LatestRc,
+			Some(version) => match release_version(version) {
+				Some(version) => Self::Exact(format!("v{version}")),
+				None if version.starts_with(|c: char| "<>=~^".contains(c) || c.is_ascii_digit()) =>
+					Self::Requirement(VersionReq::parse(version).map_err(|e| {
+						Error::Config(format!("invalid relay chain version `{version}`: {e}"))
+					})?),
+				None => Self::Exact(version.into()),
+			},
+		})
+	}
+
+	// Whether a release satisfies the selection.
+	fn matches(&self, version: &Version) -> bool {
+		match self {
+			Self::Latest => version.pre.is_empty(),
+			Self::LatestRc => true,
+			Self::Exact(exact) => format!("v{version}") == *exact,
+			Self::Requirement(requirement) => requirement.matches(version),
 		}
-		Ok(())
 	}
 }
 
-/// The source of a binary.
-#[derive(Clone, Eq, Hash, Ord, PartialEq, PartialOrd)]
-pub enum Source {
-	/// The source is a URL.
-	Url {
-		/// The name of the binary.
-		name: String,
-		/// The version of the binary.
-		version: String,
-		/// The url to download the binary.
-		url: String,
-	},
-	/// The source is a git repository.
-	Git {
-		/// The url of the repository.
-		url: Url,
-		branch: Option<String>,
-		package: String,
-		binaries: Vec<String>,
-		version: Option<String>,
-	},
+impl std::fmt::Display for RelayChainVersion {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Latest => f.write_str("latest"),
+			Self::LatestRc => f.write_str("latest-rc"),
+			Self::Exact(version) => f.write_str(version),
+			Self::Requirement(requirement) => requirement.fmt(f),
+		}
+	}
 }
 
-impl Source {
-	async fn build_binaries<'b>(
-		path: &Path,
-		package: &str,
-		names: impl Iterator<Item = (&'b String, PathBuf)>,
-		status: impl Status,
-	) -> Result<(), Error> {
-		// Build binaries and then copy to cache and target
-		let reader = cmd("cargo", vec!["build", "--release", "-p", package])
-			.dir(path)
-			.stderr_to_stdout()
-			.reader()?;
-		let mut output = std::io::BufReader::new(reader).lines();
-		while let Some(Ok(line)) = output.next() {
-			status.update(&line);
+// Parses the version of a release, as used within its tag and optionally prefixed with `v` (e.g.
+// `v1.10.0` or `v1.11.0-rc1`). Release candidates are ordered as per semantic versioning, so any
+// `rc10` would precede `rc9`.
+fn release_version(version: &str) -> Option<Version> {
+	Version::parse(version.strip_prefix('v').unwrap_or(version)).ok()
+}
+
+/// A system parachain, launched using `polkadot-parachain`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct SystemParachain {
+	/// The chain, as provided to `polkadot-parachain` (e.g. `coretime-rococo-local`).
+	pub(crate) chain: &'static str,
+	/// The identifier of the parachain.
+	pub(crate) id: u32,
+	/// The first release of `polkadot-parachain` supporting the chain.
+	since: &'static str,
+}
+
+impl SystemParachain {
+	const fn new(chain: &'static str, id: u32, since: &'static str) -> Self {
+		Self { chain, id, since }
+	}
+
+	/// Looks up a system parachain by its chain.
+	///
+	/// # Arguments
+	///
+	/// * `chain` - the chain (e.g. `coretime-rococo-local`)
+	pub(crate) fn find(chain: &str) -> Option<&'static Self> {
+		SYSTEM_PARACHAINS.iter().find(|p| p.chain == chain)
+	}
+
+	// Assigns the default identifiers of any system parachains within a network configuration
+	// which do not specify one.
+	fn assign_para_ids(config: &mut DocumentMut) {
+		let Some(tables) = config.get_mut("parachains").and_then(|p| p.as_array_of_tables_mut())
+		else {
+			return;
+		};
+		for table in tables.iter_mut().filter(|t| !t.contains_key("id")) {
+			let Some(parachain) = table.get("chain").and_then(|c| c.as_str()).and_then(Self::find)
+			else {
+				continue;
+			};
+			table.insert("id", value(parachain.id as i64));
 		}
-		for (name, dest) in names {
-			copy(path.join(format!("target/release/{name}")), dest)?;
+	}
+
+	// Verifies that a known chain can be launched by the provided version of `polkadot-parachain`.
+	// Chain specifications provided as files are accepted as is, whilst other chains (e.g.
+	// `penpal-rococo-local`) cannot be verified and are accepted with a warning.
+	fn verify(chain: &str, version: &str, warnings: &mut Vec<String>) -> Result<(), Error> {
+		if chain.ends_with(".json") {
+			return Ok(());
+		}
+		let Some(parachain) = Self::find(chain) else {
+			warnings.push(format!(
+				"{chain} is not a known system parachain, so it cannot be verified that \
+				polkadot-parachain {version} supports it"
+			));
+			return Ok(());
+		};
+		// Versions which cannot be compared (e.g. branches) are assumed to be supported
+		if let (Some(used), Some(since)) =
+			(release_version(version), release_version(parachain.since))
+		{
+			if used < since {
+				return Err(Error::Config(format!(
+					"{chain} requires polkadot-parachain {} or later, but {version} is used",
+					parachain.since
+				)));
+			}
 		}
 		Ok(())
 	}
+}
 
-	async fn download(url: &str, cache: &PathBuf) -> Result<(), Error> {
-		// Download to cache
-		let response = reqwest::get(url).await?;
-		let mut file = File::create(&cache)?;
-		file.write_all(&response.bytes().await?)?;
-		// Make executable
-		let mut perms = metadata(cache)?.permissions();
-		perms.set_mode(0o755);
-		std::fs::set_permissions(cache, perms)?;
-		Ok(())
+/// A builder for a network configuration, covering the most common topologies without requiring a
+/// configuration file: a number of relay chain validators plus a single collator per parachain.
+pub struct NetworkConfigBuilder {
+	/// The relay chain to be launched.
+	relay_chain: String,
+	/// The number of relay chain validators, if specified.
+	validators: Option<u8>,
+	/// The parachains to be launched.
+	parachains: Vec<ParachainConfig>,
+}
+
+// A parachain within a generated network configuration.
+struct ParachainConfig {
+	id: u32,
+	chain: Option<String>,
+	command: Option<String>,
+	collator: String,
+}
+
+impl NetworkConfigBuilder {
+	/// The first identifier assigned to parachains which do not specify one.
+	const FIRST_PARA_ID: u32 = 2_000;
+	/// The names of the relay chain validators, which have well-known development keys.
+	const VALIDATORS: [&'static str; 6] = ["alice", "bob", "charlie", "dave", "eve", "ferdie"];
+
+	/// The default minimum number of relay chain validators.
+	const MIN_VALIDATORS: usize = 2;
+
+	/// Creates a new builder for a `rococo-local` relay chain. Unless specified, the relay chain
+	/// has two validators, or one per parachain when launching more parachains.
+	pub fn new() -> Self {
+		Self { relay_chain: "rococo-local".into(), validators: None, parachains: Vec::new() }
 	}
 
-	/// Processes the binary source, by either downloading the binary from a url or by cloning a
-	/// git repository and building locally from the resulting source code.
+	/// Sets the number of relay chain validators.
 	///
 	/// # Arguments
 	///
-	/// * `cache` - path to the local cache
+	/// * `validators` - the number of relay chain validators
+	pub fn validators(mut self, validators: u8) -> Self {
+		self.validators = Some(validators);
+		self
+	}
+
+	/// Adds a parachain, specified as `[id=]source`, where the source is as per
+	/// `pop up parachain --parachain`: the url of a git repository, the path to a local binary, a
+	/// binary within a local image archive or a url template. The next available parachain
+	/// identifier is assigned when not specified.
+	///
+	/// # Arguments
+	///
+	/// * `parachain` - the parachain to be added
+	pub fn parachain(mut self, parachain: &str) -> Result<Self, Error> {
+		let (id, source) = parse_parachain(parachain);
+		let command = match ParachainSource::parse(source) {
+			// Local binaries are referenced by path, so the resulting config can be reused
+			Ok(ParachainSource::Local(_)) | Err(Error::ParseError(_)) => source.to_string(),
+			Ok(source) => source.name()?,
+			Err(e) => return Err(e),
+		};
+		let id = id.unwrap_or_else(|| self.next_para_id());
+		self.parachains.push(ParachainConfig {
+			id,
+			chain: None,
+			command: Some(command),
+			collator: format!("collator-{id}"),
+		});
+		Ok(self)
+	}
+
+	/// Adds a system parachain, launched using `polkadot-parachain`.
+	///
+	/// # Arguments
+	///
+	/// * `name` - the name of the system parachain (e.g. "asset-hub")
+	pub fn system_parachain(mut self, name: &str) -> Result<Self, Error> {
+		let suffix = format!("-{}", self.relay_chain);
+		let name = name.strip_suffix(&suffix).unwrap_or(name);
+		let Some(parachain) = SystemParachain::find(&format!("{name}{suffix}")) else {
+			let supported: Vec<_> =
+				SYSTEM_PARACHAINS.iter().filter_map(|p| p.chain.strip_suffix(&suffix)).collect();
+			return Err(Error::Config(format!(
+				"unsupported system parachain: {name}, expected one of: {}",
+				supported.join(", ")
+			)));
+		};
+		self.parachains.push(ParachainConfig {
+			id: parachain.id,
+			chain: Some(parachain.chain.into()),
+			command: None,
+			collator: name.to_string(),
+		});
+		Ok(self)
+	}
+
+	/// Builds the network configuration.
+	pub fn build(&self) -> Result<DocumentMut, Error> {
+		// Each parachain requires at least one validator
+		let validators =
+			self.validators.map_or(self.parachains.len().max(Self::MIN_VALIDATORS), usize::from);
+		if validators == 0 {
+			return Err(Error::Config("expected at least one relay chain validator".into()));
+		}
+		let mut config = DocumentMut::new();
+
+		let mut relay_chain = Table::new();
+		relay_chain.insert("chain", value(&self.relay_chain));
+		let mut nodes = ArrayOfTables::new();
+		for index in 0..validators {
+			let name = Self::VALIDATORS
+				.get(index)
+				.map_or_else(|| format!("validator-{}", index + 1), |n| n.to_string());
+			let mut node = Table::new();
+			node.insert("name", value(name));
+			node.insert("validator", value(true));
+			nodes.push(node);
+		}
+		relay_chain.insert("nodes", Item::ArrayOfTables(nodes));
+		config.insert("relaychain", Item::Table(relay_chain));
+
+		let mut parachains = ArrayOfTables::new();
+		for (index, parachain) in self.parachains.iter().enumerate() {
+			if self.parachains[..index].iter().any(|p| p.id == parachain.id) {
+				return Err(Error::Config(format!("duplicate parachain id: {}", parachain.id)));
+			}
+			let mut table = Table::new();
+			table.insert("id", value(parachain.id as i64));
+			if let Some(chain) = &parachain.chain {
+				table.insert("chain", value(chain));
+			}
+			if let Some(command) = &parachain.command {
+				table.insert("default_command", value(command));
+			}
+			let mut collator = Table::new();
+			collator.insert("name", value(&parachain.collator));
+			let mut collators = ArrayOfTables::new();
+			collators.push(collator);
+			table.insert("collators", Item::ArrayOfTables(collators));
+			parachains.push(table);
+		}
+		if !parachains.is_empty() {
+			config.insert("parachains", Item::ArrayOfTables(parachains));
+		}
+		Ok(config)
+	}
+
+	/// Builds the network configuration and writes it to the provided path, as TOML.
+	///
+	/// # Arguments
+	///
+	/// * `path` - the path of the resulting network configuration file
+	pub fn write(&self, path: &Path) -> Result<(), Error> {
+		if Format::detect(path, "") != Format::Toml {
+			return Err(Error::Config(format!(
+				"network configs can only be generated as TOML: {}",
+				path.display()
+			)));
+		}
+		write(path, self.build()?.to_string())?;
+		Ok(())
+	}
+
+	// Returns the next available parachain identifier.
+	fn next_para_id(&self) -> u32 {
+		(Self::FIRST_PARA_ID..)
+			.find(|id| self.parachains.iter().all(|p| p.id != *id))
+			.expect("an unused parachain id is always available")
+	}
+}
+
+impl Default for NetworkConfigBuilder {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+// The names of the nodes defined by a network configuration.
+fn node_names(network_config: &DocumentMut) -> impl Iterator<Item = String> + '_ {
+	let names = |tables: Option<&ArrayOfTables>| {
+		tables
+			.into_iter()
+			.flat_map(|t| t.iter())
+			.filter_map(|t| t.get("name").and_then(|n| n.as_str()).map(String::from))
+			.collect::<Vec<_>>()
+	};
+	let relay_chain = names(
+		network_config
+			.get("relaychain")
+			.and_then(|r| r.get("nodes"))
+			.and_then(|n| n.as_array_of_tables()),
+	);
+	let parachains = network_config
+		.get("parachains")
+		.and_then(|p| p.as_array_of_tables())
+		.into_iter()
+		.flat_map(|p| p.iter())
+		.flat_map(move |p| {
+			let collator = p.get("collator").and_then(|c| c.get("name")).and_then(|n| n.as_str());
+			collator
+				.map(String::from)
+				.into_iter()
+				.chain(names(p.get("collators").and_then(|c| c.as_array_of_tables())))
+		});
+	relay_chain.into_iter().chain(parachains)
+}
+
+// Splits a parachain argument of the form `[id=]source` into its identifier and source.
+fn parse_parachain(parachain: &str) -> (Option<u32>, &str) {
+	match parachain.split_once('=') {
+		Some((id, source)) => match id.parse::<u32>() {
+			Ok(id) => (Some(id), source),
+			Err(_) => (None, parachain),
+		},
+		None => (None, parachain),
+	}
+}
+
+// The source of a parachain binary, as specified via `pop up parachain --parachain`.
+#[derive(Debug, PartialEq)]
+enum ParachainSource {
+	/// A git repository, from which the binary is built.
+	Git(Url),
+	/// A local binary.
+	Local(PathBuf),
+	/// A binary within a local OCI/Docker image archive, specified as
+	/// `docker-archive:<path>?<binary>[#version]` or `oci-archive:<path>?<binary>[#version]`.
+	Archive { archive: PathBuf, binary: String, version: Option<String> },
+	/// A url template with `{version}` and/or `{arch}` placeholders, specified as
+	/// `<template>[?name][#version]`.
+	Template { template: String, name: String, version: Option<String> },
+	/// A local runtime WASM, launched using `polkadot-omni-node`.
+	Runtime(PathBuf),
+}
+
+impl ParachainSource {
+	/// The prefixes denoting a local image archive.
+	const ARCHIVES: [&'static str; 2] = ["docker-archive:", "oci-archive:"];
+	/// The placeholders supported within url templates.
+	const PLACEHOLDERS: [&'static str; 2] = ["{version}", "{arch}"];
+
+	// Parses the source of a parachain binary.
+	fn parse(source: &str) -> Result<Self, Error> {
+		if let Some(archive) = Self::ARCHIVES.iter().find_map(|p| source.strip_prefix(p)) {
+			let (archive, version) = split_version(archive);
+			let Some((archive, binary)) = archive.split_once('?') else {
+				return Err(Error::Config(format!(
+					"expected the path of the binary within the image, specified as a query \
+					string (e.g. `docker-archive:./image.tar?/usr/bin/node`): {source}"
+				)));
+			};
+			return Ok(Self::Archive { archive: archive.into(), binary: binary.into(), version });
+		}
+		if source.starts_with("http") && Self::PLACEHOLDERS.iter().any(|p| source.contains(p)) {
+			let (template, version) = split_version(source);
+			let (template, name) = match template.split_once('?') {
+				Some((template, name)) => (template, name.to_string()),
+				None => (template, Self::template_name(template)?),
+			};
+			return Ok(Self::Template { template: template.into(), name, version });
+		}
+		match Url::parse(source) {
+			Ok(url) => Ok(Self::Git(url)),
+			Err(_) if source.ends_with(".wasm") => Ok(Self::Runtime(source.into())),
+			Err(_) if Path::new(source).exists() => Ok(Self::Local(source.into())),
+			Err(err) => Err(Error::from(err)),
+		}
+	}
+
+	// The name of the binary.
+	fn name(&self) -> Result<String, Error> {
+		match self {
+			Self::Git(url) => Ok(Git::provider(url).name(url)?.to_string()),
+			Self::Local(path) => file_name(path),
+			Self::Archive { binary, .. } => file_name(Path::new(binary)),
+			Self::Template { name, .. } => Ok(name.clone()),
+			Self::Runtime(_) => Ok("polkadot-omni-node".into()),
+		}
+	}
+
+	// Determines the name of a binary from the file name of a url template, up to the first
+	// placeholder (e.g. `node` from `https://example.com/{version}/node-{arch}`).
+	fn template_name(template: &str) -> Result<String, Error> {
+		let file_name = template.rsplit('/').next().unwrap_or_default();
+		let name = file_name.split('{').next().unwrap_or_default();
+		let name = name.trim_end_matches(['-', '_', '.']);
+		if name.is_empty() {
+			return Err(Error::Config(format!(
+				"unable to determine the binary name from the url template, specified as a query \
+				string (e.g. `?node`): {template}"
+			)));
+		}
+		Ok(name.into())
+	}
+}
+
+// Splits a source into its location and any version, specified as `#fragment`.
+fn split_version(source: &str) -> (&str, Option<String>) {
+	match source.rsplit_once('#') {
+		Some((source, version)) => (source, Some(version.to_string())),
+		None => (source, None),
+	}
+}
+
+// The file name of a binary.
+fn file_name(path: &Path) -> Result<String, Error> {
+	path.file_name()
+		.map(|n| n.to_string_lossy().to_string())
+		.ok_or(Error::Config(format!("expected a binary: {}", path.display())))
+}
+
+/// The format of a network configuration file.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Format {
+	Toml,
+	Json,
+	Yaml,
+}
+
+impl Format {
+	/// Detects the format of a network configuration, based on its file extension or otherwise
+	/// its contents.
+	///
+	/// # Arguments
+	///
+	/// * `path` - the path to the network configuration file
+	/// * `contents` - the contents of the network configuration file
+	pub(crate) fn detect(path: &Path, contents: &str) -> Self {
+		match path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).as_deref() {
+			Some("json") => Format::Json,
+			Some("yaml") | Some("yml") => Format::Yaml,
+			Some("toml") => Format::Toml,
+			_ if contents.trim_start().starts_with('{') => Format::Json,
+			_ => Format::Toml,
+		}
+	}
+}
+
+/// Parses a network configuration, converting JSON and YAML configurations into the TOML
+/// representation used to configure the network.
+///
+/// # Arguments
+///
+/// * `path` - the path to the network configuration file
+/// * `contents` - the contents of the network configuration file
+pub(crate) fn parse_config(path: &Path, contents: &str) -> Result<DocumentMut, Error> {
+	let mut value = match Format::detect(path, contents) {
+		Format::Toml =>
+			return contents.parse::<DocumentMut>().map_err(|err| Error::TomlError(err.into())),
+		Format::Json => serde_json::from_str::<serde_json::Value>(contents)
+			.map_err(|err| Error::Config(format!("unable to parse JSON network config: {err}")))?,
+		Format::Yaml => serde_yaml_ng::from_str::<serde_json::Value>(contents)
+			.map_err(|err| Error::Config(format!("unable to parse YAML network config: {err}")))?,
+	};
+	// TOML has no representation of null, so unset values are simply omitted
+	strip_nulls(&mut value);
+	let mut document = toml_edit::ser::to_document(&value)
+		.map_err(|err| Error::Config(format!("unable to convert network config: {err}")))?;
+	normalize(document.as_table_mut());
+	Ok(document)
+}
+
+// Removes any null values from objects.
+fn strip_nulls(value: &mut serde_json::Value) {
+	match value {
+		serde_json::Value::Object(map) => {
+			map.retain(|_, v| !v.is_null());
+			map.values_mut().for_each(strip_nulls);
+		},
+		serde_json::Value::Array(values) => values.iter_mut().for_each(strip_nulls),
+		_ => {},
+	}
+}
+
+// Converts inline tables and arrays of inline tables into standard tables and arrays of tables,
+// as expected when the configuration is resolved.
+fn normalize(table: &mut Table) {
+	for (_, item) in table.iter_mut() {
+		if item.is_inline_table() {
+			if let Ok(table) = std::mem::take(item).into_table() {
+				*item = Item::Table(table);
+			}
+		} else if item
+			.as_array()
+			.map_or(false, |a| !a.is_empty() && a.iter().all(|v| v.is_inline_table()))
+		{
+			if let Ok(tables) = std::mem::take(item).into_array_of_tables() {
+				*item = Item::ArrayOfTables(tables);
+			}
+		}
+		match item {
+			Item::Table(table) => normalize(table),
+			Item::ArrayOfTables(tables) => tables.iter_mut().for_each(normalize),
+			_ => {},
+		}
+	}
+}
+
+/// A binary used to launch a node.
+pub struct Binary {
+	/// The name of a binary.
+	pub name: String,
+	version: String,
+	path: PathBuf,
+	sources: Vec<Source>,
+}
+
+impl Binary {
+	/// Sources the binary by either downloading from a url or by cloning a git repository and
+	/// building locally from the resulting source code.
+	///
+	/// # Arguments
+	///
+	/// * `cache` - path to the local cache
+	/// * `status` - used to observe status updates
+	pub async fn source(&self, cache: &PathBuf, status: impl Status) -> Result<(), Error> {
+		self.source_with(cache, status, &SourceOptions::default()).await
+	}
+
+	/// Sources the binary as per the provided options.
+	///
+	/// # Arguments
+	///
+	/// * `cache` - path to the local cache
+	/// * `status` - used to observe status updates
+	/// * `options` - the options for sourcing the binary
+	pub async fn source_with(
+		&self,
+		cache: &Path,
+		status: impl Status,
+		options: &SourceOptions,
+	) -> Result<(), Error> {
+		for source in &self.sources {
+			source.process_with(cache, status, options).await?;
+		}
+		Ok(())
+	}
+}
+
+/// The options for sourcing binaries.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SourceOptions {
+	/// The policy for retrying failed downloads.
+	pub retry: RetryPolicy,
+	/// Whether binaries built from git repositories are built incrementally, by keeping the
+	/// source checkout and build directory of each repository within the cache for subsequent
+	/// builds.
+	pub incremental: bool,
+}
+
+/// The policy for retrying failed downloads, using exponential backoff.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+	/// The maximum number of attempts.
+	pub attempts: u32,
+	/// The delay before the first retry, which doubles for each subsequent retry.
+	pub backoff: Duration,
+	/// The maximum delay between retries.
+	pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+	// The delay before retrying, after the provided number of failed attempts.
+	fn delay(&self, attempts: u32) -> Duration {
+		let factor = 2u32.saturating_pow(attempts.saturating_sub(1));
+		self.backoff.saturating_mul(factor).min(self.max_backoff)
+	}
+}
+
+impl Default for RetryPolicy {
+	fn default() -> Self {
+		Self { attempts: 3, backoff: Duration::from_secs(1), max_backoff: Duration::from_secs(30) }
+	}
+}
+
+/// The source of a binary.
+#[derive(Clone, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Source {
+	/// The source is a URL.
+	Url {
+		/// The name of the binary.
+		name: String,
+		/// The version of the binary.
+		version: String,
+		/// The url to download the binary.
+		url: String,
+		/// The expected SHA-256 digest of the binary, as hex. When not specified, any checksum
+		/// published alongside the binary (at `<url>.sha256`) is used instead.
+		digest: Option<String>,
+	},
+	/// The source is a git repository.
+	Git {
+		/// The url of the repository.
+		url: Url,
+		branch: Option<String>,
+		package: String,
+		binaries: Vec<String>,
+		version: Option<String>,
+	},
+	/// The source is a local binary, which is linked into the cache.
+	Local {
+		/// The name of the binary.
+		name: String,
+		/// The location of the binary.
+		path: PathBuf,
+	},
+	/// The source is a binary within a local OCI/Docker image archive (e.g. as output by
+	/// `docker save`).
+	Archive {
+		/// The name of the binary.
+		name: String,
+		/// The version of the binary, if any.
+		version: Option<String>,
+		/// The location of the image archive.
+		archive: PathBuf,
+		/// The path of the binary within the image (e.g. `/usr/bin/polkadot`).
+		binary: String,
+	},
+}
+
+impl Source {
+	async fn build_binaries<'b>(
+		path: &Path,
+		target_dir: &Path,
+		package: &str,
+		names: impl Iterator<Item = (&'b String, PathBuf)>,
+		status: impl Status,
+	) -> Result<(), Error> {
+		// Build binaries and then copy to cache and target
+		let reader = cmd("cargo", vec!["build", "--release", "-p", package])
+			.dir(path)
+			.env("CARGO_TARGET_DIR", target_dir)
+			.stderr_to_stdout()
+			.reader()?;
+		let mut output = std::io::BufReader::new(reader).lines();
+		while let Some(Ok(line)) = output.next() {
+			status.update(&line);
+		}
+		for (name, dest) in names {
+			copy(target_dir.join("release").join(name), dest)?;
+		}
+		Ok(())
+	}
+
+	async fn download(
+		url: &str,
+		digest: Option<&str>,
+		cache: &Path,
+		status: impl Status,
+	) -> Result<(), Error> {
+		// Determine the expected digest, falling back to any published checksum
+		let digest = match digest {
+			Some(digest) => Some(digest.to_lowercase()),
+			None => Self::published_digest(url).await?,
+		};
+		// Resume from any partial download remaining from a previous attempt
+		let partial = Self::partial(cache);
+		let mut resumed = if partial.exists() { metadata(&partial)?.len() } else { 0 };
+		let mut request = reqwest::Client::new().get(url);
+		if resumed > 0 {
+			request = request.header(RANGE, format!("bytes={resumed}-"));
+		}
+		let response = request.send().await?;
+		let mut response = match response.status() {
+			StatusCode::RANGE_NOT_SATISFIABLE => {
+				// The partial download is no longer valid, so start again
+				remove_file(&partial)?;
+				return Err(Error::Download(format!("unable to resume download of {url}")));
+			},
+			_ => response.error_for_status()?,
+		};
+		if response.status() != StatusCode::PARTIAL_CONTENT {
+			// The server does not support resumption, so the download restarts from the beginning
+			resumed = 0;
+		}
+		let total = response.content_length().map(|l| resumed + l);
+		if response
+			.headers()
+			.get(CONTENT_TYPE)
+			.and_then(|v| v.to_str().ok())
+			.is_some_and(|v| v.contains("text/html"))
+		{
+			return Err(Error::UnexpectedContent {
+				url: url.into(),
+				reason: "expected a binary but received a web page".into(),
+			});
+		}
+		// Stream to the partial download, which is only moved into the cache once complete
+		let mut hasher = Sha256::new();
+		let mut file = if resumed > 0 {
+			std::io::copy(&mut File::open(&partial)?, &mut hasher)?;
+			OpenOptions::new().append(true).open(&partial)?
+		} else {
+			File::create(&partial)?
+		};
+		let mut progress =
+			DownloadProgress { url, downloaded: resumed, resumed, total, elapsed: Duration::ZERO };
+		let started = Instant::now();
+		let mut reported = started;
+		while let Some(chunk) = response.chunk().await? {
+			if progress.downloaded == 0 && Self::is_html(&chunk) {
+				drop(file);
+				remove_file(&partial)?;
+				return Err(Error::UnexpectedContent {
+					url: url.into(),
+					reason: "expected a binary but received a web page".into(),
+				});
+			}
+			file.write_all(&chunk)?;
+			hasher.update(&chunk);
+			progress.downloaded += chunk.len() as u64;
+			// Limit the frequency of progress updates
+			if reported.elapsed() >= PROGRESS_INTERVAL {
+				progress.elapsed = started.elapsed();
+				status.progress(&progress);
+				reported = Instant::now();
+			}
+		}
+		file.flush()?;
+		drop(file);
+		progress.elapsed = started.elapsed();
+		status.progress(&progress);
+		if let Some(total) = total.filter(|t| *t != progress.downloaded) {
+			return Err(Error::Download(format!(
+				"incomplete download of {url}: received {} of {total} bytes",
+				progress.downloaded
+			)));
+		}
+		// Verify integrity before making executable
+		if let Some(expected) = digest {
+			if let Err(e) = Self::verify(url, format!("{:x}", hasher.finalize()), expected) {
+				remove_file(&partial)?;
+				return Err(e);
+			}
+		}
+		let mut perms = metadata(&partial)?.permissions();
+		perms.set_mode(0o755);
+		std::fs::set_permissions(&partial, perms)?;
+		// Finally move into the cache
+		rename(&partial, cache)?;
+		Ok(())
+	}
+
+	// Downloads the binary, retrying transient failures as per the retry policy.
+	async fn download_with_retry(
+		url: &str,
+		digest: Option<&str>,
+		cache: &Path,
+		status: impl Status,
+		retry: &RetryPolicy,
+	) -> Result<(), Error> {
+		let mut attempt = 1;
+		loop {
+			match Self::download(url, digest, cache, status).await {
+				Ok(()) => return Ok(()),
+				Err(e) if attempt < retry.attempts && Self::is_transient(&e) => {
+					let delay = retry.delay(attempt);
+					status.update(&format!(
+						"Download failed: {e}. Retrying in {}s (attempt {} of {})...",
+						delay.as_secs(),
+						attempt + 1,
+						retry.attempts
+					));
+					sleep(delay).await;
+					attempt += 1;
+				},
+				Err(e) => {
+					// Clean up any partial download
+					let partial = Self::partial(cache);
+					if partial.exists() {
+						remove_file(partial)?;
+					}
+					return Err(e);
+				},
+			}
+		}
+	}
+
+	// Whether a download error is transient, and therefore worth retrying. A checksum mismatch or
+	// unexpected content is not, as the same content would be downloaded again.
+	fn is_transient(error: &Error) -> bool {
+		match error {
+			Error::HttpError(e) => e.status().map_or(true, |s| {
+				s.is_server_error() ||
+					s == StatusCode::REQUEST_TIMEOUT ||
+					s == StatusCode::TOO_MANY_REQUESTS
+			}),
+			Error::Download(_) | Error::IO(_) => true,
+			_ => false,
+		}
+	}
+
+	// The location of a partial download of a binary, used to resume an interrupted download.
+	fn partial(path: &Path) -> PathBuf {
+		let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+		path.with_file_name(format!(".{name}.part"))
+	}
+
+	// Fetches the checksum published alongside a binary, if available.
+	async fn published_digest(url: &str) -> Result<Option<String>, Error> {
+		let response = reqwest::get(format!("{url}.sha256")).await?;
+		if !response.status().is_success() {
+			return Ok(None);
+		}
+		let contents = response.text().await?;
+		Self::parse_digest(&contents).map(Some).ok_or(Error::UnexpectedContent {
+			url: format!("{url}.sha256"),
+			reason: "expected a SHA-256 digest".into(),
+		})
+	}
+
+	// Parses a SHA-256 digest from the contents of a checksum file, in the format output by
+	// `sha256sum` (e.g. `<digest>  <file>`).
+	fn parse_digest(contents: &str) -> Option<String> {
+		let digest = contents.split_whitespace().next()?;
+		(digest.len() == 64 && digest.chars().all(|c| c.is_ascii_hexdigit()))
+			.then(|| digest.to_lowercase())
+	}
+
+	// Whether the downloaded contents are a web page, such as an error page, rather than a binary.
+	fn is_html(bytes: &[u8]) -> bool {
+		let start = bytes.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(bytes.len());
+		let prefix = String::from_utf8_lossy(&bytes[start..bytes.len().min(start + 16)]);
+		let prefix = prefix.to_lowercase();
+		prefix.starts_with("<!doctype html") || prefix.starts_with("<html")
+	}
+
+	// Verifies that the SHA-256 digest of the download matches that expected.
+	fn verify(url: &str, actual: String, expected: String) -> Result<(), Error> {
+		if actual != expected {
+			return Err(Error::ChecksumMismatch { url: url.into(), expected, actual });
+		}
+		Ok(())
+	}
+
+	/// Processes the binary source, by either downloading the binary from a url, cloning a git
+	/// repository and building locally from the resulting source code, linking a local binary or
+	/// extracting it from a local image archive. Failed downloads are retried as per the default
+	/// retry policy.
+	///
+	/// # Arguments
+	///
+	/// * `cache` - path to the local cache
 	/// * `status` - used to observe status updates
 	pub async fn process(
 		&self,
 		cache: &Path,
 		status: impl Status,
+	) -> Result<Option<Vec<PathBuf>>, Error> {
+		self.process_with(cache, status, &SourceOptions::default()).await
+	}
+
+	/// Processes the binary source, by either downloading the binary from a url, cloning a git
+	/// repository and building locally from the resulting source code, linking a local binary or
+	/// extracting it from a local image archive. Interrupted downloads are resumed from any partial
+	/// download within the cache, and git repositories are built incrementally when specified.
+	///
+	/// # Arguments
+	///
+	/// * `cache` - path to the local cache
+	/// * `status` - used to observe status updates
+	/// * `options` - the options for sourcing the binary
+	pub async fn process_with(
+		&self,
+		cache: &Path,
+		status: impl Status,
+		options: &SourceOptions,
 	) -> Result<Option<Vec<PathBuf>>, Error> {
 		// Download or clone and build from source
 		match self {
-			Source::Url { name, version, url } => {
+			Source::Url { name, version, url, digest } => {
 				// Check if source already exist within cache
-				let versioned_name = Self::versioned_name(name, Some(version));
+				let version = Some(version.as_str()).filter(|v| !v.is_empty());
+				let versioned_name = Self::versioned_name(name, version);
 				if cache.join(&versioned_name).exists() {
 					return Ok(None);
 				}
 
 				// Download required version of binaries
 				status.update(&format!("Downloading from {url}..."));
-				Self::download(&url, &cache.join(&versioned_name)).await?;
+				let path = cache.join(&versioned_name);
+				Self::download_with_retry(url, digest.as_deref(), &path, status, &options.retry)
+					.await?;
+				Cache::new(cache).record(&versioned_name, name, version, url)?;
 				Ok(None)
 			},
 			Source::Git { url, branch, package, binaries, version } => {
@@ -500,10 +2010,37 @@ impl Source {
 					return Ok(None);
 				}
 
-				let repository_name = GitHub::name(url)?;
-				let working_dir = cache.join(".src").join(repository_name);
-				let working_dir = Path::new(&working_dir);
-
+				let repository_name = Git::provider(url).name(url)?;
+				// Incremental builds keep a checkout and share a build directory per repository,
+				// whereas other builds use a temporary checkout (removed once dropped), leaving any
+				// kept checkout in place
+				let src = cache.join(".src");
+				let (checkout, working_dir, target_dir) = match options.incremental {
+					true => (
+						None,
+						src.join(repository_name),
+						cache.join(".target").join(repository_name),
+					),
+					false => {
+						create_dir_all(&src)?;
+						let prefix = format!(".{repository_name}-");
+						let checkout = Builder::new().prefix(&prefix).tempdir_in(&src)?;
+						let working_dir = checkout.path().join(repository_name);
+						let target_dir = working_dir.join("target");
+						(Some(checkout), working_dir, target_dir)
+					},
+				};
+				let working_dir = working_dir.as_path();
+
+				// Update any existing checkout to the requested version
+				if working_dir.exists() {
+					status.update(&format!("Fetching {url}..."));
+					if let Err(e) = Git::fetch_and_checkout(working_dir, branch.as_deref()) {
+						// Fall back to cloning afresh
+						status.update(&format!("Unable to update existing checkout: {e}"));
+						Self::remove(working_dir)?;
+					}
+				}
 				// Clone repository into working directory
 				if !working_dir.exists() {
 					status.update(&format!("Cloning {url}..."));
@@ -515,29 +2052,76 @@ impl Source {
 						return Err(e.into());
 					}
 				}
-				// Build binaries and finally remove working directory
-				if let Err(e) = Self::build_binaries(
+				// Build binaries, with any temporary checkout then removed
+				Self::build_binaries(
 					working_dir,
+					&target_dir,
 					package,
 					versioned_names
 						.iter()
 						.map(|(binary, versioned)| (*binary, cache.join(versioned))),
 					status,
 				)
-				.await
+				.await?;
+				if let Some(checkout) = checkout {
+					checkout.close()?;
+				}
+				let source = match branch {
+					Some(branch) => format!("{url}#{branch}"),
+					None => url.to_string(),
+				};
+				for (binary, versioned_name) in &versioned_names {
+					Cache::new(cache).record(
+						versioned_name,
+						binary,
+						version.as_deref(),
+						&source,
+					)?;
+				}
+				Ok(None)
+			},
+			Source::Local { name, path } => {
+				// Link the local binary into the cache, replacing any existing link
+				let link = cache.join(Self::versioned_name(name, Some(LOCAL_VERSION)));
+				if link.is_symlink() {
+					remove_symlink_file(&link)?;
+				}
+				status.update(&format!("Linking {}...", path.display()));
+				symlink_file(path.canonicalize()?, link)?;
+				Ok(None)
+			},
+			Source::Archive { name, version, archive, binary } => {
+				// Check if source already exist within cache
+				let versioned_name = Self::versioned_name(name, version.as_deref());
+				let path = cache.join(&versioned_name);
+				if path.exists() {
+					return Ok(None);
+				}
+
+				// Extract the binary, which is only moved into the cache once complete
+				status.update(&format!("Extracting {binary} from {}...", archive.display()));
+				let partial = Self::partial(&path);
+				if let Err(e) =
+					ImageArchive::open(archive).and_then(|a| a.extract(binary, &partial))
 				{
-					if working_dir.exists() {
-						// Preserve original error
-						let _ = Self::remove(working_dir);
+					if partial.exists() {
+						remove_file(&partial)?;
 					}
-					return Err(e.into());
+					return Err(e);
 				}
-				Self::remove(working_dir)?;
+				rename(&partial, &path)?;
+				let source = format!("{}?{binary}", archive.display());
+				Cache::new(cache).record(&versioned_name, name, version.as_deref(), &source)?;
 				Ok(None)
 			},
 		}
 	}
 
+	// Whether the binary is sourced locally, and can therefore be sourced while offline.
+	fn is_local(&self) -> bool {
+		matches!(self, Source::Local { .. } | Source::Archive { .. })
+	}
+
 	fn remove(path: &Path) -> Result<(), Error> {
 		remove_dir_all(path)?;
 		if let Some(source) = path.parent() {
@@ -566,6 +2150,35 @@ impl Source {
 pub trait Status: Copy {
 	/// Update the observer with the provided `status`.
 	fn update(&self, status: &str);
+
+	/// Update the observer with the progress of a download.
+	fn progress(&self, _progress: &DownloadProgress) {}
+}
+
+/// The progress of a download.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DownloadProgress<'a> {
+	/// The url being downloaded.
+	pub url: &'a str,
+	/// The number of bytes downloaded so far.
+	pub downloaded: u64,
+	/// The number of bytes downloaded previously, when resuming an interrupted download.
+	pub resumed: u64,
+	/// The total number of bytes to be downloaded, if known.
+	pub total: Option<u64>,
+	/// The time elapsed since the download started.
+	pub elapsed: Duration,
+}
+
+impl DownloadProgress<'_> {
+	/// The average throughput of the download so far, in bytes per second.
+	pub fn throughput(&self) -> u64 {
+		match self.elapsed.as_secs_f64() {
+			secs if secs > 0.0 =>
+				(self.downloaded.saturating_sub(self.resumed) as f64 / secs) as u64,
+			_ => 0,
+		}
+	}
 }
 
 impl Status for () {
@@ -577,82 +2190,560 @@ impl Status for () {
 mod tests {
 
 	use super::*;
+	use crate::utils::testing::{serve, serve_rpc, serve_with_headers};
 	use anyhow::Result;
+	use serde_json::json;
+	use std::sync::{Arc, OnceLock};
 
 	const CONFIG_FILE_PATH: &str = "../../tests/zombienet.toml";
 	const TESTING_POLKADOT_VERSION: &str = "v1.7.0";
-	const POLKADOT_BINARY: &str = "polkadot-v1.7.0";
-	const POLKADOT_PARACHAIN_BINARY: &str = "polkadot-parachain-v1.7.0";
+	const RELEASES: &[u8] = include_bytes!("../../../tests/github/releases.json");
+	const POLKADOT_BINARIES: [&str; 3] =
+		["polkadot", "polkadot-execute-worker", "polkadot-prepare-worker"];
+	// Pinned to a commit, so that the commit is not resolved from the remote repository
+	const POP_NODE: &str =
+		"https://github.com/r0gue-io/pop-node#0f1e2d3c4b5a69788796a5b4c3d2e1f00f1e2d3c";
+	const POP_NODE_BINARY: &str = "pop-node-0f1e2d3";
+
+	#[tokio::test]
+	async fn test_new_zombienet_success() -> Result<()> {
+		let temp_dir = tempfile::tempdir().expect("Could not create temp dir");
+		let cache = PathBuf::from(temp_dir.path());
+
+		let zombienet = Zombienet::new(
+			cache.clone(),
+			CONFIG_FILE_PATH,
+			Some(&TESTING_POLKADOT_VERSION.to_string()),
+			Some(&TESTING_POLKADOT_VERSION.to_string()),
+			Some(&vec![POP_NODE.to_string()]),
+			false,
+		)
+		.await?;
+
+		// Check has the binary for Polkadot
+		let polkadot = cached("polkadot", TESTING_POLKADOT_VERSION);
+		assert_eq!(zombienet.relay_chain.name, polkadot);
+		assert_eq!(zombienet.relay_chain.path, temp_dir.path().join(&polkadot));
+		assert_eq!(zombienet.relay_chain.version, TESTING_POLKADOT_VERSION);
+		assert_eq!(
+			zombienet.relay_chain.sources.len(),
+			sources(TESTING_POLKADOT_VERSION, &POLKADOT_BINARIES)
+		);
+
+		// Check has the binary for the System Chain
+		assert_eq!(zombienet.parachains.len(), 2);
+
+		let system_chain = &zombienet.parachains[0];
+		let polkadot_parachain = cached("polkadot-parachain", TESTING_POLKADOT_VERSION);
+		assert_eq!(system_chain.name, polkadot_parachain);
+		assert_eq!(system_chain.path, temp_dir.path().join(&polkadot_parachain));
+		assert_eq!(system_chain.version, TESTING_POLKADOT_VERSION);
+		assert_eq!(system_chain.sources.len(), 1);
+
+		// Check has the binary for POP
+		let parachain = &zombienet.parachains[1];
+		assert_eq!(parachain.name, POP_NODE_BINARY);
+		assert_eq!(parachain.path, temp_dir.path().join(POP_NODE_BINARY));
+		assert_eq!(parachain.version, "0f1e2d3");
+		assert_eq!(parachain.sources.len(), 1);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_new_offline_resolves_from_cache() -> Result<()> {
+		let temp_dir = tempfile::tempdir().expect("Could not create temp dir");
+		let cache = PathBuf::from(temp_dir.path());
+		for version in ["v1.9.0", "v1.10.0"] {
+			for binary in [
+				"polkadot",
+				"polkadot-execute-worker",
+				"polkadot-prepare-worker",
+				"polkadot-parachain",
+			] {
+				write(cache.join(cached(binary, version)), "")?;
+			}
+		}
+
+		let zombienet =
+			Zombienet::new(cache.clone(), CONFIG_FILE_PATH, None, None, None, true).await?;
+		assert_eq!(zombienet.relay_chain.version, "v1.10.0");
+		assert_eq!(zombienet.parachains[0].name, cached("polkadot-parachain", "v1.10.0"));
+		assert!(zombienet.missing_binaries().is_empty());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_new_offline_fails_when_not_cached() -> Result<()> {
+		let temp_dir = tempfile::tempdir().expect("Could not create temp dir");
+		let cache = PathBuf::from(temp_dir.path());
+
+		let result = Zombienet::new(cache.clone(), CONFIG_FILE_PATH, None, None, None, true).await;
+		assert!(
+			matches!(result, Err(Error::Offline(message)) if message.starts_with("no polkadot binary is cached"))
+		);
+
+		write(cache.join(cached("polkadot", "v1.10.0")), "")?;
+		write(cache.join(cached("polkadot-execute-worker", "v1.10.0")), "")?;
+		let result = Zombienet::new(
+			cache.clone(),
+			CONFIG_FILE_PATH,
+			None,
+			None,
+			Some(&vec![POP_NODE.to_string()]),
+			true,
+		)
+		.await;
+		let missing = format!(
+			"the following binaries are required but not cached: {}, pop-node-0f1e2d3, {}.",
+			cached("polkadot-parachain", "v1.10.0"),
+			cached("polkadot-prepare-worker", "v1.10.0")
+		);
+		assert!(matches!(
+			result,
+			Err(Error::Offline(message)) if message.starts_with(&missing)
+		));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_new_fails_wrong_config_no_para_id() -> Result<()> {
+		let temp_dir = tempfile::tempdir().expect("Could not create temp dir");
+		let cache = PathBuf::from(temp_dir.path());
+
+		let toml_file = generate_wrong_config_no_para_id(&temp_dir)
+			.expect("Error generating the testing toml file");
+		let toml_file_path =
+			toml_file.to_str().expect("Error generating the path of the testing toml file");
+
+		let result_error = Zombienet::new(
+			cache.clone(),
+			toml_file_path,
+			Some(&TESTING_POLKADOT_VERSION.to_string()),
+			Some(&TESTING_POLKADOT_VERSION.to_string()),
+			Some(&vec![POP_NODE.to_string()]),
+			false,
+		)
+		.await;
+
+		assert!(result_error.is_err());
+		let error_message = result_error.err().unwrap();
+		assert_eq!(
+			error_message.to_string(),
+			"Configuration error: expected `parachain` to have `id`"
+		);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_new_zombienet_from_json_and_yaml() -> Result<()> {
+		for config in ["../../tests/zombienet.json", "../../tests/zombienet.yaml"] {
+			let temp_dir = tempfile::tempdir().expect("Could not create temp dir");
+			let cache = PathBuf::from(temp_dir.path());
+
+			let mut zombienet = Zombienet::new(
+				cache.clone(),
+				config,
+				Some(&TESTING_POLKADOT_VERSION.to_string()),
+				Some(&TESTING_POLKADOT_VERSION.to_string()),
+				Some(&vec![POP_NODE.to_string()]),
+				false,
+			)
+			.await?;
+
+			assert_eq!(zombienet.relay_chain.name, cached("polkadot", TESTING_POLKADOT_VERSION));
+			assert_eq!(zombienet.parachains.len(), 2);
+			assert_eq!(
+				zombienet.parachains[0].name,
+				cached("polkadot-parachain", TESTING_POLKADOT_VERSION)
+			);
+			assert_eq!(zombienet.parachains[1].name, POP_NODE_BINARY);
+
+			// Resolved as if configured via TOML
+			let config = zombienet.configure()?;
+			let contents = std::fs::read_to_string(config.path())?;
+			let document = contents.parse::<DocumentMut>()?;
+			assert_eq!(
+				document["relaychain"]["default_command"].as_str(),
+				temp_dir.path().join(cached("polkadot", TESTING_POLKADOT_VERSION)).to_str()
+			);
+			assert_eq!(document["relaychain"]["nodes"].as_array_of_tables().unwrap().len(), 2);
+			let parachains = document["parachains"].as_array_of_tables().unwrap();
+			assert_eq!(
+				parachains.get(1).unwrap()["default_command"].as_str(),
+				temp_dir.path().join(POP_NODE_BINARY).to_str()
+			);
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn test_format_detect() {
+		assert_eq!(Format::detect(Path::new("network.toml"), "{}"), Format::Toml);
+		assert_eq!(Format::detect(Path::new("network.JSON"), ""), Format::Json);
+		assert_eq!(Format::detect(Path::new("network.yml"), ""), Format::Yaml);
+		assert_eq!(Format::detect(Path::new("network.yaml"), ""), Format::Yaml);
+		assert_eq!(Format::detect(Path::new("network"), "  {\"relaychain\": {}}"), Format::Json);
+		assert_eq!(Format::detect(Path::new("network"), "[relaychain]"), Format::Toml);
+	}
+
+	#[test]
+	fn test_parse_config_normalizes_tables() -> Result<()> {
+		let json = r#"{
+			"settings": { "timeout": 1000, "provider": null },
+			"relaychain": { "chain": "rococo-local", "nodes": [{ "name": "alice" }] },
+			"parachains": [{ "id": 1000, "collators": [{ "name": "collator", "args": ["-lruntime=debug"] }] }]
+		}"#;
+		let document = parse_config(Path::new("network.json"), json)?;
+		assert!(document["settings"].is_table());
+		assert!(document["settings"].get("provider").is_none());
+		assert!(document["relaychain"].is_table());
+		assert!(document["relaychain"]["nodes"].is_array_of_tables());
+		let parachains = document["parachains"].as_array_of_tables().unwrap();
+		let collators = parachains.get(0).unwrap()["collators"].as_array_of_tables().unwrap();
+		assert!(collators.get(0).unwrap()["args"].is_array());
+		Ok(())
+	}
+
+	#[test]
+	fn test_parse_config_fails_invalid_json() {
+		let result = parse_config(Path::new("network.json"), "{ \"relaychain\": ");
+		assert!(
+			matches!(result, Err(Error::Config(message)) if message.starts_with("unable to parse JSON"))
+		);
+	}
+
+	#[test]
+	fn test_network_config_builder() -> Result<()> {
+		let config = NetworkConfigBuilder::new()
+			.validators(3)
+			.system_parachain("asset-hub")?
+			.parachain("https://github.com/r0gue-io/pop-node")?
+			.parachain("2001=./target/release/parachain-template-node")?
+			.build()?;
+		assert_eq!(
+			config.to_string(),
+			r#"[relaychain]
+chain = "rococo-local"
+
+[[relaychain.nodes]]
+name = "alice"
+validator = true
+
+[[relaychain.nodes]]
+name = "bob"
+validator = true
+
+[[relaychain.nodes]]
+name = "charlie"
+validator = true
+
+[[parachains]]
+id = 1000
+chain = "asset-hub-rococo-local"
+
+[[parachains.collators]]
+name = "asset-hub"
+
+[[parachains]]
+id = 2000
+default_command = "pop-node"
+
+[[parachains.collators]]
+name = "collator-2000"
+
+[[parachains]]
+id = 2001
+default_command = "./target/release/parachain-template-node"
+
+[[parachains.collators]]
+name = "collator-2001"
+"#
+		);
+		// The generated config is valid
+		assert_eq!(crate::validate_toml(&config.to_string()), vec![]);
+		Ok(())
+	}
+
+	#[test]
+	fn test_network_config_builder_assigns_next_para_id() -> Result<()> {
+		let config = NetworkConfigBuilder::new()
+			.parachain("2000=./node")?
+			.parachain("./node")?
+			.validators(7)
+			.build()?;
+		let parachains = config["parachains"].as_array_of_tables().unwrap();
+		assert_eq!(parachains.get(1).unwrap()["id"].as_integer(), Some(2001));
+		let nodes = config["relaychain"]["nodes"].as_array_of_tables().unwrap();
+		assert_eq!(nodes.get(6).unwrap()["name"].as_str(), Some("validator-7"));
+		Ok(())
+	}
+
+	#[test]
+	fn test_network_config_builder_defaults_validator_per_parachain() -> Result<()> {
+		let validators = |builder: NetworkConfigBuilder| -> Result<usize> {
+			Ok(builder.build()?["relaychain"]["nodes"].as_array_of_tables().unwrap().len())
+		};
+		assert_eq!(validators(NetworkConfigBuilder::new())?, 2);
+		assert_eq!(validators(NetworkConfigBuilder::new().parachain("./node")?)?, 2);
+		let builder = NetworkConfigBuilder::new()
+			.system_parachain("asset-hub")?
+			.parachain("./node")?
+			.parachain("./node")?;
+		let config = builder.build()?;
+		assert_eq!(config["relaychain"]["nodes"].as_array_of_tables().unwrap().len(), 3);
+		// The generated config is valid
+		assert_eq!(crate::validate_toml(&config.to_string()), vec![]);
+		assert_eq!(validators(builder.validators(4))?, 4);
+		Ok(())
+	}
+
+	#[test]
+	fn test_network_config_builder_fails() -> Result<()> {
+		assert!(matches!(
+			NetworkConfigBuilder::new().system_parachain("unknown"),
+			Err(Error::Config(message)) if message.starts_with("unsupported system parachain: unknown")
+		));
+		// Collectives are only available on westend
+		assert!(matches!(
+			NetworkConfigBuilder::new().system_parachain("collectives"),
+			Err(Error::Config(message))
+				if message == "unsupported system parachain: collectives, expected one of: asset-hub, bridge-hub, coretime, people"
+		));
+		assert!(matches!(
+			NetworkConfigBuilder::new().validators(0).build(),
+			Err(Error::Config(message)) if message == "expected at least one relay chain validator"
+		));
+		assert!(matches!(
+			NetworkConfigBuilder::new()
+				.system_parachain("asset-hub-rococo-local")?
+				.parachain("1000=./node")?
+				.build(),
+			Err(Error::Config(message)) if message == "duplicate parachain id: 1000"
+		));
+		assert!(NetworkConfigBuilder::new().write(Path::new("network.json")).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn test_parse_parachain() {
+		assert_eq!(parse_parachain("2000=./node"), (Some(2000), "./node"));
+		assert_eq!(parse_parachain("./node"), (None, "./node"));
+		assert_eq!(parse_parachain("./a=b"), (None, "./a=b"));
+		assert_eq!(
+			parse_parachain("https://github.com/r0gue-io/pop-node#v1.0"),
+			(None, "https://github.com/r0gue-io/pop-node#v1.0")
+		);
+	}
+
+	#[test]
+	fn test_parachain_source_parse() -> Result<()> {
+		assert_eq!(
+			ParachainSource::parse("docker-archive:./image.tar?/usr/bin/pop-node#v1.0")?,
+			ParachainSource::Archive {
+				archive: "./image.tar".into(),
+				binary: "/usr/bin/pop-node".into(),
+				version: Some("v1.0".into())
+			}
+		);
+		assert!(matches!(
+			ParachainSource::parse("oci-archive:./image.tar"),
+			Err(Error::Config(e)) if e.contains("expected the path of the binary")
+		));
+		assert_eq!(
+			ParachainSource::parse("https://example.com/{version}/pop-node-{arch}.bin#v1.0")?,
+			ParachainSource::Template {
+				template: "https://example.com/{version}/pop-node-{arch}.bin".into(),
+				name: "pop-node".into(),
+				version: Some("v1.0".into())
+			}
+		);
+		assert_eq!(
+			ParachainSource::parse("https://example.com/{arch}/node?pop-node")?.name()?,
+			"pop-node"
+		);
+		assert!(matches!(
+			ParachainSource::parse("https://example.com/{version}"),
+			Err(Error::Config(e)) if e.contains("unable to determine the binary name")
+		));
+		assert_eq!(
+			ParachainSource::parse("https://github.com/r0gue-io/pop-node#v1.0")?.name()?,
+			"pop-node"
+		);
+		assert_eq!(
+			ParachainSource::parse(CONFIG_FILE_PATH)?,
+			ParachainSource::Local(CONFIG_FILE_PATH.into())
+		);
+		assert_eq!(
+			ParachainSource::parse("./runtime.compact.compressed.wasm")?,
+			ParachainSource::Runtime("./runtime.compact.compressed.wasm".into())
+		);
+		assert_eq!(ParachainSource::parse("./runtime.wasm")?.name()?, "polkadot-omni-node");
+		assert!(matches!(ParachainSource::parse("./missing"), Err(Error::ParseError(_))));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_new_zombienet_with_runtime_parachain() -> Result<()> {
+		let temp_dir = tempfile::tempdir().expect("Could not create temp dir");
+		let cache = temp_dir.path().join("cache");
+		std::fs::create_dir(&cache)?;
+		let runtime = temp_dir.path().join("runtime.wasm");
+		write(&runtime, "")?;
+		let config = temp_dir.path().join("network.toml");
+		NetworkConfigBuilder::new()
+			.parachain(&format!("2000={}", runtime.display()))?
+			.write(&config)?;
+		// A stand-in for chain-spec-builder, which records its arguments as the chain spec
+		let chain_spec_builder = cache.join(cached("chain-spec-builder", "v1.16.0"));
+		write(&chain_spec_builder, "#!/bin/sh\necho \"$@\" > \"$2\"\n")?;
+		std::fs::set_permissions(&chain_spec_builder, std::fs::Permissions::from_mode(0o755))?;
+
+		let mut zombienet = Zombienet::new_with(
+			cache.clone(),
+			config.to_str().expect("valid path"),
+			Some(&TESTING_POLKADOT_VERSION.to_string()),
+			None,
+			Some(&vec![format!("2000={}", runtime.display())]),
+			false,
+			&github(),
+		)
+		.await?;
+		// The generic collator is sourced from the first release including it
+		let parachain = &zombienet.parachains[&2000];
+		assert_eq!(parachain.name, cached("polkadot-omni-node", "v1.16.0"));
+		match published("v1.16.0", &["polkadot-omni-node"]) {
+			true => assert!(matches!(
+				&parachain.sources[..],
+				[Source::Url { url, .. }] if url.ends_with("/polkadot-v1.16.0/polkadot-omni-node")
+			)),
+			false => assert!(matches!(
+				&parachain.sources[..],
+				[Source::Git { branch: Some(branch), .. }] if branch == "release-polkadot-v1.16.0"
+			)),
+		}
+		let missing: Vec<_> = zombienet.missing_binaries().iter().map(|b| b.name.clone()).collect();
+		assert!(!missing.contains(&cached("chain-spec-builder", "v1.16.0")));
+
+		let config =
+			std::fs::read_to_string(zombienet.configure()?.path())?.parse::<DocumentMut>()?;
+		let parachain = config["parachains"].as_array_of_tables().unwrap().get(0).unwrap();
+		assert_eq!(
+			parachain["default_command"].as_str(),
+			cache.join(cached("polkadot-omni-node", "v1.16.0")).to_str()
+		);
+		let chain_spec = std::fs::read_to_string(parachain["chain_spec_path"].as_str().unwrap())?;
+		assert_eq!(
+			chain_spec.trim(),
+			format!(
+				"-c {} create --relay-chain rococo-local --para-id 2000 -r {} named-preset development",
+				parachain["chain_spec_path"].as_str().unwrap(),
+				runtime.canonicalize()?.display()
+			)
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn test_omni_node_version() {
+		let version = "stable2409".to_string();
+		assert_eq!(Zombienet::omni_node_version(Some(&version), "v1.10.0"), "stable2409");
+		assert_eq!(Zombienet::omni_node_version(None, "v1.10.0"), OMNI_NODE_MIN_VERSION);
+		assert_eq!(Zombienet::omni_node_version(None, "v1.17.0"), "v1.17.0");
+		assert_eq!(Zombienet::omni_node_version(None, "stable2409"), "stable2409");
+	}
+
+	#[test]
+	fn test_generate_chain_spec_fails() -> Result<()> {
+		let temp_dir = tempfile::tempdir().expect("Could not create temp dir");
+		let path = temp_dir.path().join("chain-spec-builder");
+		write(&path, "#!/bin/sh\necho 'unknown preset' >&2\nexit 1\n")?;
+		std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+		let binary =
+			Binary { name: "chain-spec-builder".into(), version: "".into(), path, sources: vec![] };
+		assert!(matches!(
+			Zombienet::generate_chain_spec(&binary, Path::new("runtime.wasm"), "rococo-local", 2000),
+			Err(Error::ChainSpec(message)) if message.ends_with(": unknown preset")
+		));
+		Ok(())
+	}
 
 	#[tokio::test]
-	async fn test_new_zombienet_success() -> Result<()> {
+	async fn test_new_zombienet_with_local_parachain() -> Result<()> {
 		let temp_dir = tempfile::tempdir().expect("Could not create temp dir");
-		let cache = PathBuf::from(temp_dir.path());
+		let cache = temp_dir.path().join("cache");
+		std::fs::create_dir(&cache)?;
+		let binary = temp_dir.path().join("pop-node");
+		write(&binary, "")?;
 
 		let zombienet = Zombienet::new(
 			cache.clone(),
 			CONFIG_FILE_PATH,
 			Some(&TESTING_POLKADOT_VERSION.to_string()),
-			Some(&TESTING_POLKADOT_VERSION.to_string()),
-			Some(&vec!["https://github.com/r0gue-io/pop-node".to_string()]),
+			None,
+			Some(&vec![format!("9090={}", binary.display())]),
+			false,
 		)
 		.await?;
-
-		// Check has the binary for Polkadot
-		assert_eq!(zombienet.relay_chain.name, POLKADOT_BINARY);
-		assert_eq!(zombienet.relay_chain.path, temp_dir.path().join(POLKADOT_BINARY));
-		assert_eq!(zombienet.relay_chain.version, TESTING_POLKADOT_VERSION);
-		if cfg!(target_os = "macos") {
-			assert_eq!(zombienet.relay_chain.sources.len(), 1);
-		} else {
-			assert_eq!(zombienet.relay_chain.sources.len(), 3);
-		}
-
-		// Check has the binary for the System Chain
-		assert_eq!(zombienet.parachains.len(), 2);
-
-		let system_chain = &zombienet.parachains[0];
-		assert_eq!(system_chain.name, POLKADOT_PARACHAIN_BINARY);
-		assert_eq!(system_chain.path, temp_dir.path().join(POLKADOT_PARACHAIN_BINARY));
-		assert_eq!(system_chain.version, TESTING_POLKADOT_VERSION);
-		assert_eq!(system_chain.sources.len(), 1);
-
-		// Check has the binary for POP
-		let parachain = &zombienet.parachains[1];
-		assert_eq!(parachain.name, "pop-node");
-		assert_eq!(parachain.path, temp_dir.path().join("pop-node"));
-		assert_eq!(parachain.version, "");
+		let parachain = &zombienet.parachains[&9090];
+		assert_eq!(parachain.name, "pop-node-local");
+		assert_eq!(parachain.path, cache.join("pop-node-local"));
 		assert_eq!(parachain.sources.len(), 1);
-
 		Ok(())
 	}
 
 	#[tokio::test]
-	async fn test_new_fails_wrong_config_no_para_id() -> Result<()> {
+	async fn test_local() -> Result<()> {
 		let temp_dir = tempfile::tempdir().expect("Could not create temp dir");
-		let cache = PathBuf::from(temp_dir.path());
-
-		let toml_file = generate_wrong_config_no_para_id(&temp_dir)
-			.expect("Error generating the testing toml file");
-		let toml_file_path =
-			toml_file.to_str().expect("Error generating the path of the testing toml file");
-
-		let result_error = Zombienet::new(
-			cache.clone(),
-			toml_file_path,
-			Some(&TESTING_POLKADOT_VERSION.to_string()),
-			Some(&TESTING_POLKADOT_VERSION.to_string()),
-			Some(&vec!["https://github.com/r0gue-io/pop-node".to_string()]),
-		)
-		.await;
+		let cache = temp_dir.path().join("cache");
+		std::fs::create_dir(&cache)?;
+		let binary = temp_dir.path().join("node");
+		write(&binary, "")?;
+
+		let local = Zombienet::local(&binary, &cache)?;
+		assert_eq!(local.path, cache.join("node-local"));
+		assert_eq!(local.version, LOCAL_VERSION);
+		local.source(&cache, ()).await?;
+		assert_eq!(read_link(&local.path)?, binary.canonicalize()?);
+		assert!(Zombienet::local(&binary, &cache)?.sources.is_empty());
+
+		// A binary with the same name at another location is linked afresh
+		std::fs::create_dir(temp_dir.path().join("other"))?;
+		let other = temp_dir.path().join("other/node");
+		write(&other, "")?;
+		let local = Zombienet::local(&other, &cache)?;
+		assert!(!local.path.exists());
+		local.source(&cache, ()).await?;
+		assert_eq!(read_link(&local.path)?, other.canonicalize()?);
+		Ok(())
+	}
 
-		assert!(result_error.is_err());
-		let error_message = result_error.err().unwrap();
-		assert_eq!(
-			error_message.to_string(),
-			"Configuration error: expected `parachain` to have `id`"
-		);
+	#[test]
+	fn test_template() -> Result<()> {
+		let temp_dir = tempfile::tempdir().expect("Could not create temp dir");
+		let cache = PathBuf::from(temp_dir.path());
 
+		let binary = Zombienet::template(
+			"https://example.com/{version}/node-{arch}",
+			"node",
+			Some("v1.0"),
+			&cache,
+		)?;
+		assert_eq!(binary.name, "node-v1.0");
+		assert_eq!(binary.path, cache.join("node-v1.0"));
+		assert!(matches!(
+			&binary.sources[..],
+			[Source::Url { url, .. }]
+				if *url == format!("https://example.com/v1.0/node-{}", std::env::consts::ARCH)
+		));
+
+		let binary = Zombienet::template("https://example.com/node-{arch}", "node", None, &cache)?;
+		assert_eq!(binary.path, cache.join("node"));
+		assert!(matches!(
+			Zombienet::template("https://example.com/{version}/node", "node", None, &cache),
+			Err(Error::Config(e)) if e.contains("a version is required")
+		));
 		Ok(())
 	}
 
@@ -664,19 +2755,24 @@ mod tests {
 		let network_config_path = PathBuf::from(CONFIG_FILE_PATH);
 		let config = std::fs::read_to_string(&network_config_path)?.parse::<DocumentMut>()?;
 
-		let binary_relay_chain =
-			Zombienet::relay_chain(Some(&TESTING_POLKADOT_VERSION.to_string()), &config, &cache)
-				.await?;
+		let binary_relay_chain = Zombienet::relay_chain(
+			Some(&TESTING_POLKADOT_VERSION.to_string()),
+			&config,
+			&cache,
+			false,
+			&github(),
+			&mut Vec::new(),
+		)
+		.await?;
 
-		assert_eq!(binary_relay_chain.name, POLKADOT_BINARY);
-		assert_eq!(binary_relay_chain.path, temp_dir.path().join(POLKADOT_BINARY));
+		let polkadot = cached("polkadot", TESTING_POLKADOT_VERSION);
+		assert_eq!(binary_relay_chain.name, polkadot);
+		assert_eq!(binary_relay_chain.path, temp_dir.path().join(&polkadot));
 		assert_eq!(binary_relay_chain.version, TESTING_POLKADOT_VERSION);
-
-		if cfg!(target_os = "macos") {
-			assert_eq!(binary_relay_chain.sources.len(), 1);
-		} else {
-			assert_eq!(binary_relay_chain.sources.len(), 3);
-		}
+		assert_eq!(
+			binary_relay_chain.sources.len(),
+			sources(TESTING_POLKADOT_VERSION, &POLKADOT_BINARIES)
+		);
 
 		Ok(())
 	}
@@ -689,16 +2785,13 @@ mod tests {
 		let network_config_path = PathBuf::from(CONFIG_FILE_PATH);
 		let config = std::fs::read_to_string(&network_config_path)?.parse::<DocumentMut>()?;
 
-		// Ideally here we will Mock GitHub struct and its get_latest_release function response
-		let binary_relay_chain = Zombienet::relay_chain(None, &config, &cache).await?;
+		let github = github();
+		let binary_relay_chain =
+			Zombienet::relay_chain(None, &config, &cache, false, &github, &mut Vec::new()).await?;
 
-		assert!(binary_relay_chain.name.starts_with("polkadot-v"));
-		assert!(binary_relay_chain.version.starts_with("v"));
-		if cfg!(target_os = "macos") {
-			assert_eq!(binary_relay_chain.sources.len(), 1);
-		} else {
-			assert_eq!(binary_relay_chain.sources.len(), 3);
-		}
+		assert_eq!(binary_relay_chain.name, cached("polkadot", "v1.10.0"));
+		assert_eq!(binary_relay_chain.version, "v1.10.0");
+		assert_eq!(binary_relay_chain.sources.len(), sources("v1.10.0", &POLKADOT_BINARIES));
 
 		Ok(())
 	}
@@ -713,9 +2806,15 @@ mod tests {
 
 		let config = std::fs::read_to_string(&network_config_path)?.parse::<DocumentMut>()?;
 
-		let result_error =
-			Zombienet::relay_chain(Some(&TESTING_POLKADOT_VERSION.to_string()), &config, &cache)
-				.await;
+		let result_error = Zombienet::relay_chain(
+			Some(&TESTING_POLKADOT_VERSION.to_string()),
+			&config,
+			&cache,
+			false,
+			&github(),
+			&mut Vec::new(),
+		)
+		.await;
 		assert!(result_error.is_err());
 		let error_message = result_error.err().unwrap();
 		assert_eq!(error_message.to_string(), "Configuration error: expected `relaychain`");
@@ -725,42 +2824,477 @@ mod tests {
 
 	#[tokio::test]
 	async fn test_latest_polkadot_release() -> Result<()> {
-		let version = Zombienet::latest_polkadot_release().await?;
-		// Result will change all the time to the current version (e.g: v1.9.0), check at least starts with v
-		assert!(version.starts_with("v"));
+		let github = github();
+		let latest = |version: Option<&'static str>| {
+			let github = &github;
+			async move {
+				let selection = RelayChainVersion::parse(version)?;
+				Zombienet::latest_polkadot_release(github, &selection).await
+			}
+		};
+		// Pre-releases are skipped unless requested
+		assert_eq!(latest(None).await?.as_deref(), Some("v1.10.0"));
+		assert_eq!(latest(Some("latest-rc")).await?.as_deref(), Some("v1.11.0-rc1"));
+		assert_eq!(latest(Some(">=1.9, <1.10")).await?.as_deref(), Some("v1.9.0"));
+		assert_eq!(latest(Some(">=1.11")).await?, None);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_latest_polkadot_release_pages() -> Result<()> {
+		let release = |tag: &str| json!({ "tag_name": tag, "name": tag, "prerelease": false });
+		let api = Arc::new(OnceLock::<String>::new());
+		let url = serve_with_headers({
+			let api = api.clone();
+			move |path, _| match path {
+				"/repos/paritytech/polkadot-sdk/releases?per_page=100" => {
+					let next = format!(
+						"<{}/repos/paritytech/polkadot-sdk/releases?per_page=100&page=2>; \
+						 rel=\"next\"",
+						api.get().expect("url set")
+					);
+					let releases =
+						json!([release("polkadot-stable2409"), release("polkadot-stable2407")]);
+					(200, vec![("link", next)], releases.to_string().into_bytes())
+				},
+				"/repos/paritytech/polkadot-sdk/releases?per_page=100&page=2" => {
+					let releases = json!([release("polkadot-v1.10.0"), release("polkadot-v1.9.0")]);
+					(200, Vec::new(), releases.to_string().into_bytes())
+				},
+				_ => (404, Vec::new(), Vec::new()),
+			}
+		});
+		api.set(url.clone()).expect("url not yet set");
+		let github = GitHubClient::new().with_token(None).with_api(&url);
+
+		let latest = |version: &'static str| {
+			let github = &github;
+			async move {
+				let selection = RelayChainVersion::parse(Some(version))?;
+				Zombienet::latest_polkadot_release(github, &selection).await
+			}
+		};
+		// Only the second page includes releases whose tags are semantic versions
+		assert_eq!(latest("latest").await?.as_deref(), Some("v1.10.0"));
+		assert_eq!(latest(">=1.9, <1.10").await?.as_deref(), Some("v1.9.0"));
+		assert_eq!(latest(">=1.11").await?, None);
+		Ok(())
+	}
+
+	#[test]
+	fn test_parse_relay_chain_version() -> Result<()> {
+		assert_eq!(RelayChainVersion::parse(Some("latest"))?, RelayChainVersion::Latest);
+		assert_eq!(RelayChainVersion::parse(Some("latest-rc"))?, RelayChainVersion::LatestRc);
+		assert_eq!(
+			RelayChainVersion::parse(Some("1.10.0"))?,
+			RelayChainVersion::Exact("v1.10.0".into())
+		);
+		assert!(matches!(
+			RelayChainVersion::parse(Some(">=1.9, <1.11"))?,
+			RelayChainVersion::Requirement(r) if r.to_string() == ">=1.9, <1.11"
+		));
+		// Pre-releases are only matched when requested or explicitly required
+		let matches = |selection: &str, version: &str| -> Result<bool> {
+			Ok(RelayChainVersion::parse(Some(selection))?
+				.matches(&release_version(version).expect("valid version")))
+		};
+		assert!(matches(">=1.9, <1.11", "v1.10.0")?);
+		assert!(!matches(">=1.9, <1.11", "v1.11.0")?);
+		assert!(!matches(">=1.9", "v1.11.0-rc1")?);
+		assert!(matches(">=1.11.0-rc1", "v1.11.0-rc2")?);
+		assert!(matches("latest-rc", "v1.11.0-rc1")?);
+		assert!(!matches("latest", "v1.11.0-rc1")?);
+		// Tags which are not semantic versions are passed through as is
+		assert_eq!(
+			RelayChainVersion::parse(Some("stable2409"))?,
+			RelayChainVersion::Exact("stable2409".into())
+		);
+		for invalid in [">=", ">=1.9,", "1.x.y"] {
+			assert!(
+				matches!(RelayChainVersion::parse(Some(invalid)), Err(Error::Config(_))),
+				"{invalid}"
+			);
+		}
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_relay_chain_warns_when_lookup_fails() -> Result<()> {
+		let temp_dir = tempfile::tempdir().expect("Could not create temp dir");
+		let cache = PathBuf::from(temp_dir.path());
+		let config = std::fs::read_to_string(CONFIG_FILE_PATH)?.parse::<DocumentMut>()?;
+		let url = serve(|_, _| (500, Vec::new()));
+		let github = GitHubClient::new().with_token(None).with_api(&url);
+
+		// On platforms other than the default, the lookup of the release itself is also warned of
+		let lookups = |warnings: &[String]| {
+			warnings.iter().filter(|w| w.contains("unable to look up polkadot releases")).count()
+		};
+		let mut warnings = Vec::new();
+		let binary =
+			Zombienet::relay_chain(None, &config, &cache, false, &github, &mut warnings).await?;
+		assert_eq!(binary.version, POLKADOT_DEFAULT_VERSION);
+		assert_eq!(lookups(&warnings), 1);
+
+		// A cached release satisfying a requirement is used instead, otherwise the lookup fails
+		let requirement = ">=1.7, <1.8".to_string();
+		let result = Zombienet::relay_chain(
+			Some(&requirement),
+			&config,
+			&cache,
+			false,
+			&github,
+			&mut warnings,
+		)
+		.await;
+		assert!(result.is_err());
+		write(cache.join(cached("polkadot", TESTING_POLKADOT_VERSION)), "")?;
+		let binary = Zombienet::relay_chain(
+			Some(&requirement),
+			&config,
+			&cache,
+			false,
+			&github,
+			&mut warnings,
+		)
+		.await?;
+		assert_eq!(binary.version, TESTING_POLKADOT_VERSION);
+		assert_eq!(lookups(&warnings), 2);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_new_resolves_latest_polkadot_release() -> Result<()> {
+		let temp_dir = tempfile::tempdir().expect("Could not create temp dir");
+		let cache = PathBuf::from(temp_dir.path());
+
+		let zombienet = Zombienet::new_with(
+			cache.clone(),
+			CONFIG_FILE_PATH,
+			None,
+			None,
+			Some(&vec![POP_NODE.to_string()]),
+			false,
+			&github(),
+		)
+		.await?;
+
+		assert_eq!(zombienet.relay_chain.name, cached("polkadot", "v1.10.0"));
+		assert_eq!(
+			zombienet.parachains.get(&1000).unwrap().name,
+			cached("polkadot-parachain", "v1.10.0")
+		);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_new_resolves_system_parachains() -> Result<()> {
+		let temp_dir = tempfile::tempdir().expect("Could not create temp dir");
+		let cache = PathBuf::from(temp_dir.path());
+		let config = temp_dir.path().join("network.toml");
+		write(
+			&config,
+			r#"[relaychain]
+chain = "rococo-local"
+
+[[relaychain.nodes]]
+name = "alice"
+validator = true
+
+[[parachains]]
+chain = "coretime-rococo-local"
+
+[[parachains.collators]]
+name = "coretime"
+
+[[parachains]]
+id = 2004
+chain = "people-rococo-local"
+
+[[parachains.collators]]
+name = "people"
+"#,
+		)?;
+		let config = config.to_str().expect("valid path");
+		let version = "v1.10.0".to_string();
+
+		let zombienet = Zombienet::new_with(
+			cache.clone(),
+			config,
+			Some(&version),
+			None,
+			None,
+			false,
+			&github(),
+		)
+		.await?;
+		// The default para id is assigned when omitted
+		assert_eq!(zombienet.parachains.keys().copied().collect::<Vec<_>>(), [1005, 2004]);
+		let parachains = zombienet.network_config.1["parachains"].as_array_of_tables().unwrap();
+		assert_eq!(parachains.get(0).unwrap()["id"].as_integer(), Some(1005));
+
+		// The chains must be supported by the version of polkadot-parachain
+		let system_parachain_version = "v1.7.0".to_string();
+		assert!(matches!(
+			Zombienet::new_with(
+				cache,
+				config,
+				Some(&version),
+				Some(&system_parachain_version),
+				None,
+				false,
+				&github(),
+			)
+			.await,
+			Err(Error::Config(message))
+				if message == "people-rococo-local requires polkadot-parachain v1.8.0 or later, but v1.7.0 is used"
+		));
 		Ok(())
 	}
 
+	#[test]
+	fn test_verify_system_parachain() {
+		let mut warnings = Vec::new();
+		assert!(SystemParachain::verify("coretime-rococo-local", "v1.7.0", &mut warnings).is_ok());
+		assert!(matches!(
+			SystemParachain::verify("coretime-rococo-local", "v1.6.0", &mut warnings),
+			Err(Error::Config(message))
+				if message == "coretime-rococo-local requires polkadot-parachain v1.7.0 or later, but v1.6.0 is used"
+		));
+		// Versions which cannot be compared and chain specifications are accepted
+		assert!(SystemParachain::verify("coretime-rococo-local", "master", &mut warnings).is_ok());
+		assert!(SystemParachain::verify("./chain-spec.json", "v1.0.0", &mut warnings).is_ok());
+		assert!(warnings.is_empty());
+		// Chains which are not known are accepted, with a warning
+		assert!(SystemParachain::verify("penpal-rococo-local", "v1.10.0", &mut warnings).is_ok());
+		assert!(matches!(
+			&warnings[..],
+			[w] if w.starts_with("penpal-rococo-local is not a known system parachain")
+		));
+	}
+
+	// The name under which a binary of a polkadot-sdk release is cached on the host, including
+	// the architecture when not the default (e.g. `polkadot-v1.10.0+aarch64`).
+	fn cached(binary: &str, version: &str) -> String {
+		Source::versioned_name(binary, Some(&Platform::HOST.cached_version(version)))
+	}
+
+	// Whether artifacts of the binaries of a polkadot-sdk release are published for the host:
+	// always for the default platform, otherwise only when included in the assets of the release
+	// within the fixture.
+	fn published(version: &str, binaries: &[&str]) -> bool {
+		if Platform::HOST.is_default() {
+			return true;
+		}
+		let releases: Vec<serde_json::Value> =
+			serde_json::from_slice(RELEASES).expect("valid releases");
+		let Some(release) = releases.iter().find(|r| r["tag_name"] == format!("polkadot-{version}"))
+		else {
+			return false;
+		};
+		let assets = release["assets"].as_array().cloned().unwrap_or_default();
+		binaries.iter().all(|b| {
+			Platform::HOST
+				.artifact(b)
+				.is_some_and(|a| assets.iter().any(|asset| asset["name"] == a.as_str()))
+		})
+	}
+
+	// The number of sources of the binaries of a polkadot-sdk release on the host: one per
+	// binary when their artifacts are published, otherwise a single build of the release branch.
+	fn sources(version: &str, binaries: &[&str]) -> usize {
+		match published(version, binaries) {
+			true => binaries.len(),
+			false => 1,
+		}
+	}
+
+	// A client for a local stand-in of the GitHub api, serving the releases of the polkadot-sdk
+	// repository from a fixture.
+	fn github() -> GitHubClient {
+		let url = serve(|path, _| match path {
+			"/repos/paritytech/polkadot-sdk/releases?per_page=100" => (200, RELEASES.to_vec()),
+			path => {
+				let releases: Vec<serde_json::Value> =
+					serde_json::from_slice(RELEASES).expect("valid releases");
+				path.strip_prefix("/repos/paritytech/polkadot-sdk/releases/tags/")
+					.and_then(|tag| releases.into_iter().find(|r| r["tag_name"] == tag))
+					.map_or((404, Vec::new()), |r| (200, r.to_string().into_bytes()))
+			},
+		});
+		GitHubClient::new().with_token(None).with_api(&url)
+	}
+
 	#[tokio::test]
 	async fn test_system_parachain() -> Result<()> {
 		let temp_dir = tempfile::tempdir().expect("Could not create temp dir");
 		let cache = PathBuf::from(temp_dir.path());
 
 		let binary_system_chain =
-			Zombienet::system_parachain(&TESTING_POLKADOT_VERSION.to_string(), &cache)?;
-
-		assert_eq!(binary_system_chain.name, POLKADOT_PARACHAIN_BINARY);
-		assert_eq!(binary_system_chain.path, temp_dir.path().join(POLKADOT_PARACHAIN_BINARY));
+			Zombienet::system_parachain(
+				&TESTING_POLKADOT_VERSION.to_string(),
+				&cache,
+				&github(),
+				&mut Vec::new(),
+			)
+			.await?;
+
+		let polkadot_parachain = cached("polkadot-parachain", TESTING_POLKADOT_VERSION);
+		assert_eq!(binary_system_chain.name, polkadot_parachain);
+		assert_eq!(binary_system_chain.path, temp_dir.path().join(&polkadot_parachain));
 		assert_eq!(binary_system_chain.version, TESTING_POLKADOT_VERSION);
 		assert_eq!(binary_system_chain.sources.len(), 1);
 
 		Ok(())
 	}
 
+	#[test]
+	fn test_platform() {
+		let linux = Platform { os: "linux", arch: "x86_64" };
+		let macos = Platform { os: "macos", arch: "aarch64" };
+		let linux_arm = Platform { os: "linux", arch: "aarch64" };
+		assert_eq!(linux.artifact("polkadot").as_deref(), Some("polkadot"));
+		assert_eq!(macos.artifact("polkadot").as_deref(), Some("polkadot-aarch64-apple-darwin"));
+		assert_eq!(linux_arm.artifact("polkadot"), None);
+		// Binaries for the default architecture keep their existing names within the cache
+		assert_eq!(linux.cached_version("v1.10.0"), "v1.10.0");
+		assert_eq!(macos.cached_version("v1.10.0"), "v1.10.0+aarch64");
+		assert_eq!(linux.version("v1.10.0"), Some("v1.10.0"));
+		assert_eq!(linux.version("v1.10.0+aarch64"), None);
+		assert_eq!(linux_arm.version("v1.10.0+aarch64"), Some("v1.10.0"));
+		assert_eq!(linux_arm.version("v1.10.0"), None);
+	}
+
+	#[tokio::test]
+	async fn test_release_sources() -> Result<()> {
+		const BINARIES: [&str; 2] = ["polkadot", "polkadot-prepare-worker"];
+		let github = github();
+		let sources = |platform, version| {
+			let github = &github;
+			async move {
+				let mut warnings = Vec::new();
+				let sources = Zombienet::release_sources(
+					platform,
+					github,
+					version,
+					"polkadot",
+					&BINARIES,
+					&mut warnings,
+				)
+				.await;
+				assert!(warnings.is_empty());
+				sources
+			}
+		};
+
+		// Release artifacts are used when published for the platform
+		let macos = Platform { os: "macos", arch: "aarch64" };
+		let release_sources = sources(macos, "v1.10.0").await;
+		assert_eq!(release_sources.len(), BINARIES.len());
+		assert!(matches!(
+			&release_sources[0],
+			Source::Url { name, version, url, .. }
+				if name == "polkadot" && version == "v1.10.0+aarch64" &&
+					url.ends_with("/polkadot-v1.10.0/polkadot-aarch64-apple-darwin")
+		));
+
+		// Otherwise the release branch is built
+		for (platform, version) in [
+			(macos, "v1.9.0"),
+			(Platform { os: "linux", arch: "aarch64" }, "v1.10.0"),
+			(Platform { os: "windows", arch: "x86_64" }, "v1.10.0"),
+		] {
+			let release_sources = sources(platform, version).await;
+			let expected = platform.cached_version(version);
+			assert!(matches!(
+				&release_sources[..],
+				[Source::Git { branch: Some(branch), binaries, version: Some(v), .. }]
+					if *branch == format!("release-polkadot-{version}") &&
+						binaries.len() == BINARIES.len() && *v == expected
+			));
+		}
+
+		// Artifacts are assumed to be published for linux x86_64, without a lookup
+		let linux = Platform { os: "linux", arch: "x86_64" };
+		let client = GitHubClient::new().with_token(None).with_api("http://127.0.0.1:1");
+		let mut warnings = Vec::new();
+		let release_sources = Zombienet::release_sources(
+			linux,
+			&client,
+			"v1.9.0",
+			"polkadot",
+			&BINARIES,
+			&mut warnings,
+		)
+		.await;
+		assert!(matches!(
+			&release_sources[..],
+			[Source::Url { version, url, .. }, _]
+				if version == "v1.9.0" && url.ends_with("/polkadot-v1.9.0/polkadot")
+		));
+		assert!(warnings.is_empty());
+
+		// A failed lookup falls back to building the release branch, with a warning
+		let release_sources = Zombienet::release_sources(
+			macos,
+			&client,
+			"v1.10.0",
+			"polkadot",
+			&BINARIES,
+			&mut warnings,
+		)
+		.await;
+		assert!(matches!(&release_sources[..], [Source::Git { .. }]));
+		assert!(matches!(
+			&warnings[..],
+			[w] if w.contains("unable to look up the polkadot-v1.10.0 release")
+		));
+		Ok(())
+	}
+
 	#[tokio::test]
 	async fn test_parachain() -> Result<()> {
 		let temp_dir = tempfile::tempdir().expect("Could not create temp dir");
 		let cache = PathBuf::from(temp_dir.path());
 
-		let url = Url::parse("https://github.com/r0gue-io/pop-node")?;
+		const COMMIT: &str = "3a4b5c6d7e8f90123456789abcdef0123456789a";
+		let url = Url::parse(&format!("https://github.com/r0gue-io/pop-node#{COMMIT}"))?;
 
-		let binary_system_chain = Zombienet::parachain(url, &cache)?;
+		let binary_system_chain = Zombienet::parachain(url, &cache, false)?;
 
-		assert_eq!(binary_system_chain.name, "pop-node");
-		assert_eq!(binary_system_chain.path, temp_dir.path().join("pop-node"));
-		assert_eq!(binary_system_chain.version, "");
-		assert_eq!(binary_system_chain.sources.len(), 1);
+		assert_eq!(binary_system_chain.name, "pop-node-3a4b5c6");
+		assert_eq!(binary_system_chain.path, temp_dir.path().join("pop-node-3a4b5c6"));
+		assert_eq!(binary_system_chain.version, "3a4b5c6");
+		assert!(matches!(
+			&binary_system_chain.sources[..],
+			[Source::Git { branch: Some(branch), version: Some(version), .. }]
+				if branch == COMMIT && version == "3a4b5c6"
+		));
+
+		Ok(())
+	}
 
+	#[test]
+	fn test_parachain_offline_uses_latest_cached_build() -> Result<()> {
+		let temp_dir = tempfile::tempdir().expect("Could not create temp dir");
+		let cache = PathBuf::from(temp_dir.path());
+		const REPO: &str = "https://github.com/r0gue-io/pop-node";
+		let url = Url::parse(&format!("{REPO}#main"))?;
+		assert!(matches!(Zombienet::parachain(url.clone(), &cache, true), Err(Error::Offline(_))));
+
+		for (version, last_used) in [("1111111", 2), ("2222222", 1)] {
+			let name = format!("pop-node-{version}");
+			write(cache.join(&name), "")?;
+			write(
+				cache.join(format!(".{name}.json")),
+				format!(
+					r#"{{"name":"pop-node","version":"{version}","source":"{REPO}#{version}","last_used":{last_used}}}"#
+				),
+			)?;
+		}
+		let binary = Zombienet::parachain(url, &cache, true)?;
+		assert_eq!(binary.path, cache.join("pop-node-1111111"));
+		assert!(binary.sources.is_empty());
 		Ok(())
 	}
 
@@ -774,7 +3308,8 @@ mod tests {
 			CONFIG_FILE_PATH,
 			Some(&TESTING_POLKADOT_VERSION.to_string()),
 			Some(&TESTING_POLKADOT_VERSION.to_string()),
-			Some(&vec!["https://github.com/r0gue-io/pop-node".to_string()]),
+			Some(&vec![POP_NODE.to_string()]),
+			false,
 		)
 		.await?;
 
@@ -790,11 +3325,13 @@ mod tests {
 		let cache = PathBuf::from(temp_dir.path());
 
 		// Create "fake" binary files
-		let relay_chain_file_path = temp_dir.path().join(POLKADOT_BINARY);
+		let relay_chain_file_path =
+			temp_dir.path().join(cached("polkadot", TESTING_POLKADOT_VERSION));
 		File::create(relay_chain_file_path)?;
-		let system_chain_file_path = temp_dir.path().join(POLKADOT_PARACHAIN_BINARY);
+		let system_chain_file_path =
+			temp_dir.path().join(cached("polkadot-parachain", TESTING_POLKADOT_VERSION));
 		File::create(system_chain_file_path)?;
-		let pop_file_path = temp_dir.path().join("pop-node");
+		let pop_file_path = temp_dir.path().join(POP_NODE_BINARY);
 		File::create(pop_file_path)?;
 
 		let zombienet = Zombienet::new(
@@ -802,7 +3339,8 @@ mod tests {
 			CONFIG_FILE_PATH,
 			Some(&TESTING_POLKADOT_VERSION.to_string()),
 			Some(&TESTING_POLKADOT_VERSION.to_string()),
-			Some(&vec!["https://github.com/r0gue-io/pop-node".to_string()]),
+			Some(&vec![POP_NODE.to_string()]),
+			false,
 		)
 		.await?;
 
@@ -822,7 +3360,8 @@ mod tests {
 			CONFIG_FILE_PATH,
 			Some(&TESTING_POLKADOT_VERSION.to_string()),
 			Some(&TESTING_POLKADOT_VERSION.to_string()),
-			Some(&vec!["https://github.com/r0gue-io/pop-node".to_string()]),
+			Some(&vec![POP_NODE.to_string()]),
+			false,
 		)
 		.await?;
 
@@ -830,6 +3369,153 @@ mod tests {
 		Ok(())
 	}
 
+	#[tokio::test]
+	async fn test_restore_snapshot() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let cache = temp_dir.path().join("cache");
+		std::fs::create_dir(&cache)?;
+		let config = temp_dir.path().join("network.toml");
+		let base_dir = temp_dir.path().join("network");
+		write(
+			&config,
+			format!(
+				"[settings]\nbase_dir = \"{}\"\n\n[relaychain]\nchain = \"rococo-local\"\n\n\
+				[[relaychain.nodes]]\nname = \"alice\"\n\n[[relaychain.nodes]]\nname = \"bob\"\n",
+				base_dir.display()
+			),
+		)?;
+		let (version, github) = (TESTING_POLKADOT_VERSION.to_string(), github());
+		let zombienet = || {
+			let config = config.to_str().expect("valid path");
+			Zombienet::new_with(cache.clone(), config, Some(&version), None, None, false, &github)
+		};
+		// A snapshot of a network with only some of the nodes configured
+		let network = temp_dir.path().join("snapshot");
+		std::fs::create_dir_all(network.join("alice/data"))?;
+		let snapshot = temp_dir.path().join("snapshot.tar.gz");
+		crate::create_snapshot(&network, &snapshot)?;
+		assert!(matches!(
+			zombienet().await?.restore(&snapshot),
+			Err(Error::Config(message)) if message.ends_with("no state found for: bob")
+		));
+
+		// The restore can be retried once the snapshot matches
+		std::fs::create_dir_all(network.join("bob/data"))?;
+		crate::create_snapshot(&network, &snapshot)?;
+		let mut zombienet = zombienet().await?;
+		assert_eq!(zombienet.restore(&snapshot)?, base_dir);
+		assert!(base_dir.join("bob/data").is_dir());
+		let config =
+			std::fs::read_to_string(zombienet.configure()?.path())?.parse::<DocumentMut>()?;
+		assert_eq!(config["settings"]["base_dir"].as_str(), base_dir.to_str());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_fork() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let cache = temp_dir.path().join("cache");
+		std::fs::create_dir(&cache)?;
+		// A stand-in for a parachain binary, which outputs a raw chain spec
+		let node = temp_dir.path().join("node");
+		write(
+			&node,
+			"#!/bin/sh\necho '{\"name\":\"Local\",\"genesis\":{\"raw\":{\"top\":{\":code\":\"0x00\"}}}}'\n",
+		)?;
+		std::fs::set_permissions(&node, std::fs::Permissions::from_mode(0o755))?;
+		let config = temp_dir.path().join("network.toml");
+		write(
+			&config,
+			format!(
+				"[relaychain]\nchain = \"rococo-local\"\n\n[[parachains]]\nid = 2000\n\
+				default_command = \"{}\"\n",
+				node.display()
+			),
+		)?;
+		let live = serve_rpc(|method, _| match method {
+			"chain_getFinalizedHead" => json!(format!("0x{}", "ab".repeat(32))),
+			"state_getKeysPaged" => json!([":code"]),
+			"state_queryStorageAt" => json!([{ "changes": [[":code", "0xff"]] }]),
+			_ => serde_json::Value::Null,
+		});
+
+		let mut zombienet = Zombienet::new_with(
+			cache.clone(),
+			config.to_str().expect("valid path"),
+			Some(&TESTING_POLKADOT_VERSION.to_string()),
+			None,
+			None,
+			false,
+			&github(),
+		)
+		.await?;
+		assert!(matches!(
+			zombienet.fork(&format!("2001={live}"), ()).await,
+			Err(Error::Config(message)) if message == "cannot fork parachain 2001: not found"
+		));
+		assert_eq!(zombienet.fork(&live, ()).await?, 2000);
+		let config =
+			std::fs::read_to_string(zombienet.configure()?.path())?.parse::<DocumentMut>()?;
+		let parachain = config["parachains"].as_array_of_tables().unwrap().get(0).unwrap();
+		let chain_spec: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(
+			parachain["chain_spec_path"].as_str().unwrap(),
+		)?)?;
+		assert_eq!(chain_spec["genesis"]["raw"]["top"][":code"], "0xff");
+		assert_eq!(chain_spec["para_id"], 2000);
+		assert_eq!(chain_spec["relay_chain"], "rococo-local");
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_fork_uses_collator_command() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let cache = temp_dir.path().join("cache");
+		std::fs::create_dir(&cache)?;
+		let node = temp_dir.path().join("node");
+		write(
+			&node,
+			"#!/bin/sh\necho '{\"name\":\"Local\",\"genesis\":{\"raw\":{\"top\":{}}}}'\n",
+		)?;
+		std::fs::set_permissions(&node, std::fs::Permissions::from_mode(0o755))?;
+		let live = serve_rpc(|method, _| match method {
+			"chain_getFinalizedHead" => json!(format!("0x{}", "ab".repeat(32))),
+			"state_getKeysPaged" => json!([":code"]),
+			"state_queryStorageAt" => json!([{ "changes": [[":code", "0xff"]] }]),
+			_ => serde_json::Value::Null,
+		});
+
+		// The command of a collator is used in the absence of a default command
+		let config = temp_dir.path().join("network.toml");
+		write(
+			&config,
+			format!(
+				"[relaychain]\nchain = \"rococo-local\"\n\n[[parachains]]\nid = 2000\n\n\
+				[[parachains.collators]]\nname = \"alice\"\n\n\
+				[[parachains.collators]]\nname = \"bob\"\ncommand = \"{}\"\n",
+				node.display()
+			),
+		)?;
+		let mut zombienet = Zombienet::new_with(
+			cache.clone(),
+			config.to_str().expect("valid path"),
+			Some(&TESTING_POLKADOT_VERSION.to_string()),
+			None,
+			None,
+			false,
+			&github(),
+		)
+		.await?;
+		assert_eq!(zombienet.fork(&live, ()).await?, 2000);
+		let config =
+			std::fs::read_to_string(zombienet.configure()?.path())?.parse::<DocumentMut>()?;
+		let parachain = config["parachains"].as_array_of_tables().unwrap().get(0).unwrap();
+		let chain_spec: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(
+			parachain["chain_spec_path"].as_str().unwrap(),
+		)?)?;
+		assert_eq!(chain_spec["genesis"]["raw"]["top"][":code"], "0xff");
+		Ok(())
+	}
+
 	#[tokio::test]
 	async fn test_spawn_error_no_binaries() -> Result<()> {
 		let temp_dir = tempfile::tempdir().expect("Could not create temp dir");
@@ -840,7 +3526,8 @@ mod tests {
 			CONFIG_FILE_PATH,
 			Some(&TESTING_POLKADOT_VERSION.to_string()),
 			Some(&TESTING_POLKADOT_VERSION.to_string()),
-			Some(&vec!["https://github.com/r0gue-io/pop-node".to_string()]),
+			Some(&vec![POP_NODE.to_string()]),
+			false,
 		)
 		.await?;
 
@@ -858,11 +3545,50 @@ mod tests {
 		let source = Source::Url {
 			name: "polkadot".to_string(),
 			version: TESTING_POLKADOT_VERSION.to_string(),
-			url: "https://github.com/paritytech/polkadot-sdk/releases/download/polkadot-v1.7.0/polkadot".to_string()
+			url: "https://github.com/paritytech/polkadot-sdk/releases/download/polkadot-v1.7.0/polkadot".to_string(),
+			digest: None,
 		};
 		source.process(&cache, ()).await?;
-		assert!(temp_dir.path().join(POLKADOT_BINARY).exists());
+		assert!(temp_dir.path().join("polkadot-v1.7.0").exists());
+
+		Ok(())
+	}
 
+	#[tokio::test]
+	async fn test_process_git_keeps_incremental_checkout() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		// A repository containing a binary without any dependencies
+		let origin = temp_dir.path().join("node");
+		std::fs::create_dir_all(origin.join("src"))?;
+		write(
+			origin.join("Cargo.toml"),
+			"[package]\nname = \"node\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[workspace]\n",
+		)?;
+		write(origin.join("src/main.rs"), "fn main() {}\n")?;
+		let repo = git2::Repository::init(&origin)?;
+		let mut index = repo.index()?;
+		index.add_all(["*"], git2::IndexAddOption::DEFAULT, None)?;
+		let tree = repo.find_tree(index.write_tree()?)?;
+		let signature = git2::Signature::now("pop", "pop@r0gue.io")?;
+		repo.commit(Some("HEAD"), &signature, &signature, "node", &tree, &[])?;
+
+		// A checkout kept by a previous incremental build
+		let cache = temp_dir.path().join("cache");
+		let checkout = cache.join(".src/node");
+		std::fs::create_dir_all(&checkout)?;
+		write(checkout.join("kept"), "")?;
+
+		let source = Source::Git {
+			url: Url::from_directory_path(&origin).expect("valid path"),
+			branch: None,
+			package: "node".into(),
+			binaries: vec!["node".into()],
+			version: Some("v0.1.0".into()),
+		};
+		source.process_with(&cache, (), &SourceOptions::default()).await?;
+		assert!(cache.join("node-v0.1.0").exists());
+		assert!(checkout.join("kept").exists());
+		assert_eq!(std::fs::read_dir(cache.join(".src"))?.count(), 1);
 		Ok(())
 	}
 
@@ -870,13 +3596,213 @@ mod tests {
 	fn test_versioned_name() -> Result<()> {
 		let versioned_name =
 			Source::versioned_name("polkadot", Some(&TESTING_POLKADOT_VERSION.to_string()));
-		assert_eq!(versioned_name, POLKADOT_BINARY);
+		assert_eq!(versioned_name, "polkadot-v1.7.0");
 
 		let versioned_name_no_version = Source::versioned_name("polkadot", None);
 		assert_eq!(versioned_name_no_version, "polkadot");
 		Ok(())
 	}
 
+	#[test]
+	fn test_parse_digest() {
+		const DIGEST: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+		assert_eq!(Source::parse_digest(&format!("{DIGEST}  polkadot\n")), Some(DIGEST.into()));
+		assert_eq!(Source::parse_digest(&DIGEST.to_uppercase()), Some(DIGEST.into()));
+		assert_eq!(Source::parse_digest(""), None);
+		assert_eq!(Source::parse_digest("<!DOCTYPE html>"), None);
+		assert_eq!(Source::parse_digest(&DIGEST[1..]), None);
+	}
+
+	#[test]
+	fn test_is_html() {
+		assert!(Source::is_html(b"<!DOCTYPE html><html></html>"));
+		assert!(Source::is_html(b"\n  <html lang=\"en\">"));
+		assert!(!Source::is_html(b"\x7fELF\x02\x01\x01"));
+		assert!(!Source::is_html(b""));
+	}
+
+	#[test]
+	fn test_verify() {
+		const URL: &str = "https://example.com/polkadot";
+		const DIGEST: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+		assert!(Source::verify(URL, DIGEST.into(), DIGEST.into()).is_ok());
+		assert!(matches!(
+			Source::verify(URL, DIGEST[1..].into(), DIGEST.into()),
+			Err(Error::ChecksumMismatch { url, expected, actual })
+				if url == URL && expected == DIGEST && actual != DIGEST
+		));
+	}
+
+	#[tokio::test]
+	async fn test_download_fails_checksum_mismatch() -> Result<()> {
+		const DIGEST: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+		let url = serve(|path, _| match path {
+			"/polkadot.sha256" => (200, format!("{DIGEST}  polkadot").into_bytes()),
+			_ => (200, b"a tampered binary".to_vec()),
+		});
+		let url = format!("{url}/polkadot");
+		let temp_dir = tempfile::tempdir()?;
+		let path = temp_dir.path().join("polkadot");
+		// Both an expected digest and any published checksum are verified
+		for digest in [Some(DIGEST), None] {
+			assert!(matches!(
+				Source::download(&url, digest, &path, ()).await,
+				Err(Error::ChecksumMismatch { expected, .. }) if expected == DIGEST
+			));
+			// The download is removed rather than cached
+			assert!(!path.exists());
+			assert!(!Source::partial(&path).exists());
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn test_retry_policy_delay() {
+		let retry = RetryPolicy::default();
+		assert_eq!(retry.delay(1), Duration::from_secs(1));
+		assert_eq!(retry.delay(2), Duration::from_secs(2));
+		assert_eq!(retry.delay(3), Duration::from_secs(4));
+		assert_eq!(retry.delay(10), Duration::from_secs(30));
+	}
+
+	#[tokio::test]
+	async fn test_download_resumes_partial_download() -> Result<()> {
+		const CONTENTS: &[u8] = b"the contents of a binary, downloaded in two parts";
+		let url = serve(|_, range| {
+			let start = range.unwrap_or(0) as usize;
+			(if start > 0 { 206 } else { 200 }, CONTENTS[start..].to_vec())
+		});
+		let temp_dir = tempfile::tempdir()?;
+		let path = temp_dir.path().join("polkadot");
+		write(Source::partial(&path), &CONTENTS[..10])?;
+		let digest = format!("{:x}", Sha256::digest(CONTENTS));
+
+		Source::download(&format!("{url}/polkadot"), Some(&digest), &path, ()).await?;
+		assert_eq!(std::fs::read(&path)?, CONTENTS);
+		assert_eq!(metadata(&path)?.permissions().mode() & 0o777, 0o755);
+		assert!(!Source::partial(&path).exists());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_download_retries_and_removes_partial_download() -> Result<()> {
+		let requests = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+		let counter = requests.clone();
+		let url = serve(move |_, _| {
+			counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+			(500, Vec::new())
+		});
+		let temp_dir = tempfile::tempdir()?;
+		let path = temp_dir.path().join("polkadot");
+		write(Source::partial(&path), b"partial")?;
+		let retry = RetryPolicy {
+			attempts: 3,
+			backoff: Duration::from_millis(1),
+			max_backoff: Duration::from_millis(1),
+		};
+
+		let result = Source::download_with_retry(
+			&format!("{url}/polkadot"),
+			Some("digest"),
+			&path,
+			(),
+			&retry,
+		)
+		.await;
+		assert!(matches!(result, Err(Error::HttpError(_))));
+		assert_eq!(requests.load(std::sync::atomic::Ordering::SeqCst), 3);
+		assert!(!path.exists());
+		assert!(!Source::partial(&path).exists());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_download_does_not_retry_missing_binary() -> Result<()> {
+		let url = serve(|_, _| (404, Vec::new()));
+		let temp_dir = tempfile::tempdir()?;
+		let path = temp_dir.path().join("polkadot");
+		let start = Instant::now();
+		let result = Source::download_with_retry(
+			&format!("{url}/polkadot"),
+			None,
+			&path,
+			(),
+			&RetryPolicy::default(),
+		)
+		.await;
+		assert!(
+			matches!(result, Err(Error::HttpError(e)) if e.status() == Some(StatusCode::NOT_FOUND))
+		);
+		assert!(start.elapsed() < RetryPolicy::default().backoff);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_download_does_not_retry_checksum_mismatch() -> Result<()> {
+		let requests = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+		let counter = requests.clone();
+		let url = serve(move |_, _| {
+			counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+			(200, b"a tampered binary".to_vec())
+		});
+		let temp_dir = tempfile::tempdir()?;
+		let path = temp_dir.path().join("polkadot");
+		let digest = format!("{:x}", Sha256::digest(b"a binary"));
+		let result = Source::download_with_retry(
+			&format!("{url}/polkadot"),
+			Some(&digest),
+			&path,
+			(),
+			&RetryPolicy::default(),
+		)
+		.await;
+		assert!(matches!(result, Err(Error::ChecksumMismatch { .. })));
+		assert_eq!(requests.load(std::sync::atomic::Ordering::SeqCst), 1);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_download_does_not_retry_web_page() -> Result<()> {
+		let requests = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+		let counter = requests.clone();
+		let url = serve(move |_, _| {
+			counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+			(200, b"<!DOCTYPE html><html>Sign in</html>".to_vec())
+		});
+		let temp_dir = tempfile::tempdir()?;
+		let path = temp_dir.path().join("polkadot");
+		let url = format!("{url}/polkadot");
+		let result =
+			Source::download_with_retry(&url, Some("digest"), &path, (), &RetryPolicy::default())
+				.await;
+		assert!(matches!(
+			result,
+			Err(Error::UnexpectedContent { url: u, reason })
+				if u == url && reason == "expected a binary but received a web page"
+		));
+		assert_eq!(requests.load(std::sync::atomic::Ordering::SeqCst), 1);
+		assert!(!path.exists());
+		assert!(!Source::partial(&path).exists());
+		Ok(())
+	}
+
+	#[test]
+	fn test_download_progress_throughput() {
+		let mut progress = DownloadProgress {
+			url: "https://example.com/polkadot",
+			downloaded: 3_000,
+			resumed: 0,
+			total: Some(10_000),
+			elapsed: Duration::from_millis(1_500),
+		};
+		assert_eq!(progress.throughput(), 2_000);
+		// Only bytes downloaded since resuming are considered
+		progress.resumed = 1_500;
+		assert_eq!(progress.throughput(), 1_000);
+		progress.elapsed = Duration::ZERO;
+		assert_eq!(progress.throughput(), 0);
+	}
+
 	fn generate_wrong_config_no_para_id(temp_dir: &tempfile::TempDir) -> Result<PathBuf> {
 		let file_path = temp_dir.path().join("wrong_config_no_para_id.toml");
 		let mut file = File::create(file_path.clone())?;
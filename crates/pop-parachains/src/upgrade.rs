@@ -0,0 +1,287 @@
+// SPDX-License-Identifier: GPL-3.0
+use crate::{errors::Error, up::Status};
+use futures::{Stream, StreamExt};
+use serde::Deserialize;
+use std::{path::Path, pin::pin, str::FromStr, time::Duration};
+use subxt::{
+	backend::rpc::{rpc_params, RpcClient},
+	dynamic::Value,
+	events::EventDetails,
+	ext::scale_value::{Composite, ValueDef},
+	OnlineClient, PolkadotConfig,
+};
+use subxt_signer::{sr25519::Keypair, SecretUri};
+use tokio::time::timeout;
+
+/// The maximum time to wait for a scheduled runtime upgrade to be applied.
+const UPGRADE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// The outcome of a runtime upgrade.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuntimeUpgrade {
+	/// The spec version of the runtime prior to the upgrade.
+	pub previous_spec_version: u32,
+	/// The spec version of the upgraded runtime.
+	pub spec_version: u32,
+}
+
+/// Upgrades the runtime of a chain, by submitting `System.set_code` via
+/// `Sudo.sudo_unchecked_weight` from the sudo account. The upgrade is then awaited: parachains
+/// apply a runtime upgrade once permitted by the relay chain, signalled by
+/// `ParachainSystem.ValidationFunctionApplied`, whereas other chains apply it immediately,
+/// signalled by `System.CodeUpdated`. Finally, the spec version of the upgraded runtime is
+/// verified to have been incremented.
+///
+/// # Arguments
+///
+/// * `url` - the websocket uri of a node of the chain (e.g. `ws://127.0.0.1:9944`)
+/// * `wasm` - the runtime to be upgraded to
+/// * `suri` - the secret uri of the sudo account (e.g. `//Alice`)
+/// * `status` - used to observe status updates
+pub async fn upgrade_runtime(
+	url: &str,
+	wasm: &Path,
+	suri: &str,
+	status: impl Status,
+) -> Result<RuntimeUpgrade, Error> {
+	let code = std::fs::read(wasm)?;
+	let signer = SecretUri::from_str(suri)
+		.map_err(|e| e.to_string())
+		.and_then(|uri| Keypair::from_uri(&uri).map_err(|e| e.to_string()))
+		.map_err(|e| Error::Config(format!("invalid secret uri: {e}")))?;
+	let rpc_client = RpcClient::from_url(url).await?;
+	let previous_spec_version = spec_version(&rpc_client).await?;
+
+	let client = OnlineClient::<PolkadotConfig>::from_url(url).await?;
+	let set_code = subxt::dynamic::tx("System", "set_code", vec![Value::from_bytes(code)]);
+	let weight =
+		Value::named_composite([("ref_time", Value::u128(0)), ("proof_size", Value::u128(0))]);
+	let call =
+		subxt::dynamic::tx("Sudo", "sudo_unchecked_weight", vec![set_code.into_value(), weight]);
+	status.update(&format!("Submitting runtime upgrade from spec version {previous_spec_version}"));
+	// Finalized blocks are subscribed to prior to submission, so that the upgrade being applied in
+	// a block finalized before the submission is reported as finalized is not missed.
+	let blocks = client.blocks().subscribe_finalized().await?;
+	let events = client
+		.tx()
+		.sign_and_submit_then_watch_default(&call, &signer)
+		.await?
+		.wait_for_finalized_success()
+		.await?;
+	match stage(events.iter())? {
+		Some(Stage::Applied) => {},
+		Some(Stage::Scheduled) => {
+			status.update("Waiting for the relay chain to permit the runtime upgrade");
+			let stages = blocks.then(|block| async move { stage(block?.events().await?.iter()) });
+			timeout(UPGRADE_TIMEOUT, wait_until_applied(pin!(stages))).await.map_err(|_| {
+				Error::Upgrade(format!(
+					"the runtime upgrade was not applied within {} seconds",
+					UPGRADE_TIMEOUT.as_secs()
+				))
+			})??;
+		},
+		None => return Err(Error::Upgrade("the runtime upgrade was not scheduled".into())),
+	}
+
+	verify_spec_version(previous_spec_version, spec_version(&rpc_client).await?)
+}
+
+// The stages of a runtime upgrade, ordered by progression.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Stage {
+	// The upgrade has been scheduled, pending permission from the relay chain.
+	Scheduled,
+	// The upgrade has been applied.
+	Applied,
+}
+
+// The furthest stage of a runtime upgrade signalled by a sequence of events, if any.
+fn stage(
+	events: impl Iterator<Item = Result<EventDetails<PolkadotConfig>, subxt::Error>>,
+) -> Result<Option<Stage>, Error> {
+	let mut stage = None;
+	for event in events {
+		let event = event?;
+		stage = stage.max(interpret(
+			event.pallet_name(),
+			event.variant_name(),
+			&event.field_values()?,
+		)?);
+	}
+	Ok(stage)
+}
+
+// Interprets an event in terms of the stage of a runtime upgrade it signals, failing if the upgrade
+// was rejected when dispatched via sudo or discarded by the relay chain.
+fn interpret<T>(
+	pallet: &str,
+	variant: &str,
+	fields: &Composite<T>,
+) -> Result<Option<Stage>, Error> {
+	match (pallet, variant) {
+		("Sudo", "Sudid") => sudo_result(fields).map(|_| None),
+		("ParachainSystem", "ValidationFunctionStored") => Ok(Some(Stage::Scheduled)),
+		("ParachainSystem", "ValidationFunctionApplied") | ("System", "CodeUpdated") =>
+			Ok(Some(Stage::Applied)),
+		("ParachainSystem", "ValidationFunctionDiscarded") =>
+			Err(Error::Upgrade("the runtime upgrade was discarded by the relay chain".into())),
+		_ => Ok(None),
+	}
+}
+
+// Checks the result of a call dispatched via sudo, as reported by the fields of a `Sudo.Sudid`
+// event.
+fn sudo_result<T>(fields: &Composite<T>) -> Result<(), Error> {
+	let Composite::Named(fields) = fields else {
+		return Ok(());
+	};
+	match fields.iter().find(|(name, _)| name == "sudo_result").map(|(_, v)| &v.value) {
+		Some(ValueDef::Variant(result)) if result.name == "Err" => Err(Error::Upgrade(format!(
+			"the runtime upgrade was rejected: {}",
+			result.values.values().map(|v| v.to_string()).collect::<Vec<_>>().join(", ")
+		))),
+		_ => Ok(()),
+	}
+}
+
+// Waits until a runtime upgrade has been applied, given the stages signalled by each finalized
+// block.
+async fn wait_until_applied(
+	mut stages: impl Stream<Item = Result<Option<Stage>, Error>> + Unpin,
+) -> Result<(), Error> {
+	while let Some(stage) = stages.next().await {
+		if stage? == Some(Stage::Applied) {
+			return Ok(());
+		}
+	}
+	Err(Error::Upgrade("the subscription to finalized blocks ended".into()))
+}
+
+// Verifies that the spec version of the upgraded runtime was incremented.
+fn verify_spec_version(
+	previous_spec_version: u32,
+	spec_version: u32,
+) -> Result<RuntimeUpgrade, Error> {
+	if spec_version <= previous_spec_version {
+		return Err(Error::Upgrade(format!(
+			"the spec version was not incremented: {spec_version} (previously \
+			 {previous_spec_version})"
+		)));
+	}
+	Ok(RuntimeUpgrade { previous_spec_version, spec_version })
+}
+
+// The spec version of the current runtime of a chain.
+async fn spec_version(client: &RpcClient) -> Result<u32, Error> {
+	#[derive(Deserialize)]
+	#[serde(rename_all = "camelCase")]
+	struct RuntimeVersion {
+		spec_version: u32,
+	}
+	let version: RuntimeVersion = client.request("state_getRuntimeVersion", rpc_params![]).await?;
+	Ok(version.spec_version)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::utils::testing::serve_rpc;
+	use anyhow::Result;
+	use serde_json::json;
+
+	#[tokio::test]
+	async fn test_spec_version() -> Result<()> {
+		let url = serve_rpc(|method, _| match method {
+			"state_getRuntimeVersion" => json!({ "specName": "pop", "specVersion": 101 }),
+			_ => serde_json::Value::Null,
+		});
+		assert_eq!(spec_version(&RpcClient::from_url(url).await?).await?, 101);
+		Ok(())
+	}
+
+	#[test]
+	fn test_interpret() -> Result<()> {
+		let sudid = |result: Value| Composite::Named(vec![("sudo_result".into(), result)]);
+		let none = Composite::<()>::Unnamed(Vec::new());
+		assert_eq!(interpret("Sudo", "Sudid", &sudid(Value::variant("Ok", none.clone())))?, None);
+		assert!(matches!(
+			interpret(
+				"Sudo",
+				"Sudid",
+				&sudid(Value::variant("Err", Composite::Unnamed(vec![Value::string("BadOrigin")])))
+			),
+			Err(Error::Upgrade(message)) if message.starts_with("the runtime upgrade was rejected")
+		));
+		assert_eq!(
+			interpret("ParachainSystem", "ValidationFunctionStored", &none)?,
+			Some(Stage::Scheduled)
+		);
+		assert_eq!(
+			interpret("ParachainSystem", "ValidationFunctionApplied", &none)?,
+			Some(Stage::Applied)
+		);
+		assert_eq!(interpret("System", "CodeUpdated", &none)?, Some(Stage::Applied));
+		assert!(matches!(
+			interpret("ParachainSystem", "ValidationFunctionDiscarded", &none),
+			Err(Error::Upgrade(message)) if message.ends_with("discarded by the relay chain")
+		));
+		assert_eq!(interpret("Balances", "Transfer", &none)?, None);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_wait_until_applied() -> Result<()> {
+		let stages = |stages: Vec<Result<Option<Stage>, Error>>| futures::stream::iter(stages);
+		wait_until_applied(stages(vec![
+			Ok(None),
+			Ok(Some(Stage::Scheduled)),
+			Ok(Some(Stage::Applied)),
+		]))
+		.await?;
+		assert!(matches!(
+			wait_until_applied(stages(vec![
+				Ok(None),
+				Err(Error::Upgrade("the runtime upgrade was discarded by the relay chain".into())),
+				Ok(Some(Stage::Applied)),
+			]))
+			.await,
+			Err(Error::Upgrade(message)) if message.ends_with("discarded by the relay chain")
+		));
+		assert!(matches!(
+			wait_until_applied(stages(vec![Ok(Some(Stage::Scheduled))])).await,
+			Err(Error::Upgrade(message)) if message.ends_with("subscription to finalized blocks ended")
+		));
+		Ok(())
+	}
+
+	#[test]
+	fn test_verify_spec_version() -> Result<()> {
+		assert_eq!(
+			verify_spec_version(100, 101)?,
+			RuntimeUpgrade { previous_spec_version: 100, spec_version: 101 }
+		);
+		for spec_version in [99, 100] {
+			assert!(matches!(
+				verify_spec_version(100, spec_version),
+				Err(Error::Upgrade(message)) if message.starts_with("the spec version was not incremented")
+			));
+		}
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_upgrade_runtime_fails() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let wasm = temp_dir.path().join("runtime.compact.compressed.wasm");
+		assert!(matches!(
+			upgrade_runtime("ws://127.0.0.1:9944", &wasm, "//Alice", ()).await,
+			Err(Error::IO(_))
+		));
+		std::fs::write(&wasm, [0u8; 8])?;
+		assert!(matches!(
+			upgrade_runtime("ws://127.0.0.1:9944", &wasm, "//Alice//", ()).await,
+			Err(Error::Config(message)) if message.starts_with("invalid secret uri")
+		));
+		Ok(())
+	}
+}
@@ -2,18 +2,28 @@
 use crate::errors::Error;
 use anyhow::Result;
 use git2::{
-	build::RepoBuilder, FetchOptions, IndexAddOption, RemoteCallbacks, Repository, ResetType,
+	build::{CheckoutBuilder, RepoBuilder},
+	Direction, FetchOptions, IndexAddOption, Oid, Remote, RemoteCallbacks, Repository, ResetType,
 };
 use git2_credentials::CredentialHandler;
 use regex::Regex;
-use std::path::Path;
-use std::{env, fs};
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use std::path::{Path, PathBuf};
+use std::{env, fs, future::Future, pin::Pin, time::Duration};
 use url::Url;
 
 pub struct Git;
 impl Git {
 	pub(crate) fn clone(url: &Url, working_dir: &Path, branch: Option<&str>) -> Result<()> {
+		let provider = Self::provider(url);
+		let url = &provider.clone_url(url)?;
 		if !working_dir.exists() {
+			// A commit cannot be cloned directly, so is fetched into a new repository instead
+			if let Some(commit) = branch.filter(|b| Self::is_commit(b)) {
+				Repository::init(working_dir)?.remote("origin", url.as_str())?;
+				return Self::fetch_and_checkout(working_dir, Some(commit));
+			}
 			let mut fo = FetchOptions::new();
 			fo.depth(1);
 			let mut repo = RepoBuilder::new();
@@ -21,14 +31,16 @@ impl Git {
 			if let Some(branch) = branch {
 				repo.branch(branch);
 			}
-			if let Err(_e) = repo.clone(url.as_str(), working_dir) {
-				Self::ssh_clone(url, working_dir, branch)?;
+			if let Err(e) = repo.clone(url.as_str(), working_dir) {
+				match provider.ssh_url(url) {
+					Some(ssh_url) => Self::ssh_clone(&ssh_url, working_dir, branch)?,
+					None => return Err(e.into()),
+				}
 			}
 		}
 		Ok(())
 	}
-	pub(crate) fn ssh_clone(url: &Url, working_dir: &Path, branch: Option<&str>) -> Result<()> {
-		let ssh_url = GitHub::convert_to_ssh_url(url);
+	pub(crate) fn ssh_clone(ssh_url: &str, working_dir: &Path, branch: Option<&str>) -> Result<()> {
 		if !working_dir.exists() {
 			// Prepare callback and fetch options.
 			let mut fo = FetchOptions::new();
@@ -39,10 +51,154 @@ impl Git {
 			if let Some(branch) = branch {
 				repo.branch(branch);
 			}
-			repo.clone(&ssh_url, working_dir)?;
+			repo.clone(ssh_url, working_dir)?;
+		}
+		Ok(())
+	}
+	/// Fetches a reference (branch, tag or commit) from the `origin` remote of an existing
+	/// repository and checks it out, or the default branch of the remote when not specified.
+	///
+	/// # Arguments
+	///
+	/// * `working_dir` - the location of the repository
+	/// * `reference` - the branch, tag or commit to be checked out
+	pub(crate) fn fetch_and_checkout(working_dir: &Path, reference: Option<&str>) -> Result<()> {
+		let repo = Repository::open(working_dir)?;
+		let mut remote = repo.find_remote("origin")?;
+		let mut fo = FetchOptions::new();
+		fo.remote_callbacks(Self::credentials()?);
+		// Fetch and then check out the resulting commit
+		let commit = match reference {
+			Some(commit) if Self::is_full_commit(commit) => {
+				remote.fetch(&[commit], Some(fo.depth(1)), None)?;
+				repo.revparse_single(commit)?
+			},
+			// An abbreviated commit can only be resolved once all branches and tags are fetched,
+			// including any history omitted by previous shallow fetches
+			Some(commit) if Self::is_commit(commit) => {
+				if repo.is_shallow() {
+					fo.depth(i32::MAX);
+				}
+				remote.fetch(
+					&["+refs/heads/*:refs/remotes/origin/*", "+refs/tags/*:refs/tags/*"],
+					Some(&mut fo),
+					None,
+				)?;
+				repo.revparse_single(commit)
+					.map_err(|_| Error::Git(format!("unable to resolve commit {commit}")))?
+			},
+			_ => {
+				// Resolve the reference against those advertised by the remote
+				let advertised = Self::advertised(&mut remote, reference)?;
+				let Some((name, oid)) = advertised else {
+					return Err(Error::Git(match reference {
+						Some(reference) => format!("unable to resolve {reference}"),
+						None => "the remote has no default branch".into(),
+					})
+					.into());
+				};
+				remote.fetch(&[&name], Some(fo.depth(1)), None)?;
+				repo.find_object(oid, None)?
+			},
 		}
+		.peel_to_commit()?;
+		repo.checkout_tree(commit.as_object(), Some(CheckoutBuilder::new().force()))?;
+		repo.set_head_detached(commit.id())?;
 		Ok(())
 	}
+
+	/// Resolves a reference (branch or tag) of a remote repository to the commit it currently
+	/// refers to, or that of the default branch when not specified. A full commit hash is returned
+	/// as is, as is a reference which is not advertised by the remote but is an abbreviated commit
+	/// hash, which is only resolved once fetched.
+	///
+	/// # Arguments
+	///
+	/// * `url` - the url of the repository
+	/// * `reference` - the branch, tag or commit to be resolved
+	pub(crate) fn resolve_commit(url: &Url, reference: Option<&str>) -> Result<String> {
+		if let Some(commit) = reference.filter(|r| Self::is_full_commit(r)) {
+			return Ok(commit.to_lowercase());
+		}
+		let clone_url = Self::provider(url).clone_url(url)?;
+		let mut remote = Remote::create_detached(clone_url.as_str())?;
+		match Self::advertised(&mut remote, reference)? {
+			Some((_, oid)) => Ok(oid.to_string()),
+			None => match reference {
+				Some(reference) if Self::is_commit(reference) => Ok(reference.to_lowercase()),
+				_ => Err(Error::Git(format!(
+					"unable to resolve {} of {url}",
+					reference.unwrap_or("the default branch")
+				))
+				.into()),
+			},
+		}
+	}
+
+	/// Determines the provider hosting a repository, from an explicit `<provider>+` scheme prefix
+	/// (e.g. `gitlab+https://git.example.com/org/repository`), the scheme or the host, defaulting
+	/// to GitHub.
+	///
+	/// # Arguments
+	///
+	/// * `url` - the url of the repository
+	pub fn provider(url: &Url) -> &'static dyn GitProvider {
+		if let Some((provider, _)) = url.scheme().split_once('+') {
+			match provider {
+				GitLab::PREFIX => return &GitLab,
+				Gitea::PREFIX => return &Gitea,
+				_ => {},
+			}
+		}
+		if url.scheme() == "file" {
+			return &LocalRepository;
+		}
+		match url.host_str().unwrap_or_default() {
+			GitHub::GITHUB => &GitHub,
+			host if host == GitLab::GITLAB || host.starts_with("gitlab.") => &GitLab,
+			host if host == Gitea::CODEBERG || host.starts_with("gitea.") => &Gitea,
+			_ => &GitHub,
+		}
+	}
+
+	/// Whether a reference is a commit hash, either in full or abbreviated to at least seven
+	/// characters.
+	///
+	/// # Arguments
+	///
+	/// * `reference` - the reference to be checked
+	pub(crate) fn is_commit(reference: &str) -> bool {
+		(7..=40).contains(&reference.len()) && reference.chars().all(|c| c.is_ascii_hexdigit())
+	}
+
+	// Whether a reference is a full commit hash.
+	fn is_full_commit(reference: &str) -> bool {
+		reference.len() == 40 && Self::is_commit(reference)
+	}
+
+	// Finds a reference (branch or tag) amongst those advertised by a remote, returning its name
+	// and the commit it refers to. The default branch is used when no reference is specified.
+	fn advertised(remote: &mut Remote, reference: Option<&str>) -> Result<Option<(String, Oid)>> {
+		remote.connect_auth(Direction::Fetch, Some(Self::credentials()?), None)?;
+		let candidates = match reference {
+			Some(reference) =>
+				vec![format!("refs/heads/{reference}"), format!("refs/tags/{reference}")],
+			None => vec!["HEAD".to_string()],
+		};
+		let heads = remote.list()?;
+		let advertised = candidates.into_iter().find_map(|name| {
+			// Prefer the commit of an annotated tag over the tag itself
+			let peeled = format!("{name}^{{}}");
+			heads
+				.iter()
+				.find(|h| h.name() == peeled)
+				.or_else(|| heads.iter().find(|h| h.name() == name))
+				.map(|h| (name, h.oid()))
+		});
+		remote.disconnect()?;
+		Ok(advertised)
+	}
+
 	/// Clone `url` into `target` and degit it
 	pub fn clone_and_degit(
 		url: &str,
@@ -84,7 +240,9 @@ impl Git {
 
 	/// For users that have ssh configuration for cloning repositories
 	fn ssh_clone_and_degit(url: Url, target: &Path) -> Result<Repository> {
-		let ssh_url = GitHub::convert_to_ssh_url(&url);
+		let ssh_url = Self::provider(&url)
+			.ssh_url(&url)
+			.ok_or(Error::Git(format!("{url} cannot be cloned over ssh")))?;
 		// Prepare callback and fetch options.
 		let mut fo = FetchOptions::new();
 		Self::set_up_ssh_fetch_options(&mut fo)?;
@@ -96,6 +254,12 @@ impl Git {
 	}
 
 	fn set_up_ssh_fetch_options(fo: &mut FetchOptions) -> Result<()> {
+		fo.remote_callbacks(Self::credentials()?);
+		Ok(())
+	}
+
+	// Callbacks providing credentials from the git configuration.
+	fn credentials() -> Result<RemoteCallbacks<'static>> {
 		let mut callbacks = RemoteCallbacks::new();
 		let git_config = git2::Config::open_default()
 			.map_err(|e| Error::Config(format!("Cannot open git configuration: {}", e)))?;
@@ -103,9 +267,7 @@ impl Git {
 		callbacks.credentials(move |url, username, allowed| {
 			ch.try_next_credential(url, username, allowed)
 		});
-
-		fo.remote_callbacks(callbacks);
-		Ok(())
+		Ok(callbacks)
 	}
 
 	/// Fetch the latest release from a repository
@@ -145,29 +307,137 @@ impl Git {
 pub struct GitHub;
 impl GitHub {
 	const GITHUB: &'static str = "github.com";
-	pub async fn get_latest_releases(repo: &Url) -> Result<Vec<Release>> {
-		static APP_USER_AGENT: &str =
-			concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
 
-		let client = reqwest::ClientBuilder::new().user_agent(APP_USER_AGENT).build()?;
-		let response = client
-			.get(format!(
-				"https://api.github.com/repos/{}/{}/releases",
-				Self::org(repo)?,
-				Self::name(repo)?
-			))
-			.send()
-			.await?;
-		Ok(response.json::<Vec<Release>>().await?)
-	}
-
-	pub async fn get_latest_n_releases(number: usize, repo: &Url) -> Result<Vec<Release>> {
-		static APP_USER_AGENT: &str =
-			concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+	pub(crate) fn release(repo: &Url, tag: &str, artifact: &str) -> String {
+		format!("{}/releases/download/{tag}/{artifact}", repo.as_str())
+	}
+}
 
-		let client = reqwest::ClientBuilder::new().user_agent(APP_USER_AGENT).build()?;
+/// A client for the GitHub api, authenticating requests when a token is available and optionally
+/// caching responses on disk.
+pub struct GitHubClient {
+	/// The url of the api.
+	api: String,
+	/// The token used to authenticate requests.
+	token: Option<String>,
+	/// The directory within which responses are cached.
+	cache: Option<PathBuf>,
+}
+
+impl Default for GitHubClient {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl GitHubClient {
+	const API: &'static str = "https://api.github.com";
+	/// The duration for which cached responses are used without querying the api again.
+	pub const TTL: Duration = Duration::from_secs(15 * 60);
+	/// The number of items requested per page, the maximum permitted by the api.
+	const PER_PAGE: usize = 100;
+
+	/// A new client, authenticating requests using the token from the `GITHUB_TOKEN` environment
+	/// variable or the `github.token` git configuration value, if any.
+	pub fn new() -> Self {
+		let token = env::var("GITHUB_TOKEN").ok().or_else(|| {
+			git2::Config::open_default().and_then(|c| c.get_string("github.token")).ok()
+		});
+		Self { api: Self::API.into(), token: token.filter(|t| !t.is_empty()), cache: None }
+	}
+
+	/// Sends requests to the api at the specified url, such as that of a GitHub Enterprise server
+	/// or a local stand-in.
+	///
+	/// # Arguments
+	///
+	/// * `api` - the url of the api
+	pub fn with_api(mut self, api: &str) -> Self {
+		self.api = api.trim_end_matches('/').into();
+		self
+	}
 
-		let mut releases: Vec<Release> = Self::get_latest_releases(repo)
+	/// Authenticates requests using the specified token, if any.
+	///
+	/// # Arguments
+	///
+	/// * `token` - the token used to authenticate requests
+	pub fn with_token(mut self, token: Option<String>) -> Self {
+		self.token = token;
+		self
+	}
+
+	/// Caches responses within the `.github` directory of the specified cache, so that repeated
+	/// lookups within [`GitHubClient::TTL`] do not query the api again.
+	///
+	/// # Arguments
+	///
+	/// * `cache` - the location of the cache
+	pub fn with_cache(mut self, cache: &Path) -> Self {
+		self.cache = Some(cache.join(".github"));
+		self
+	}
+
+	/// Looks up the releases of a repository, newest first.
+	///
+	/// # Arguments
+	///
+	/// * `repo` - the url of the repository
+	pub async fn get_latest_releases(&self, repo: &Url) -> Result<Vec<Release>> {
+		self.get_releases_until(repo, |_| true).await
+	}
+
+	/// Looks up the releases of a repository, newest first, a page at a time until those looked up
+	/// so far satisfy `done` or no pages remain.
+	///
+	/// # Arguments
+	///
+	/// * `repo` - the url of the repository
+	/// * `done` - whether the releases looked up so far are sufficient
+	pub async fn get_releases_until(
+		&self,
+		repo: &Url,
+		done: impl Fn(&[Release]) -> bool,
+	) -> Result<Vec<Release>> {
+		let mut releases = Vec::new();
+		let mut page = Some(format!(
+			"repos/{}/{}/releases?per_page={}",
+			GitHub.org(repo)?,
+			GitHub.name(repo)?,
+			Self::PER_PAGE
+		));
+		while let Some(path) = page {
+			let (mut items, next): (Vec<Release>, _) = self.get_page(&path).await?;
+			releases.append(&mut items);
+			if done(&releases) {
+				break;
+			}
+			page = next;
+		}
+		Ok(releases)
+	}
+
+	/// Looks up the release of a repository with the specified tag.
+	///
+	/// # Arguments
+	///
+	/// * `repo` - the url of the repository
+	/// * `tag` - the tag of the release
+	pub async fn get_release(&self, repo: &Url, tag: &str) -> Result<Release> {
+		self.get(&format!("repos/{}/{}/releases/tags/{tag}", GitHub.org(repo)?, GitHub.name(repo)?))
+			.await
+	}
+
+	/// Looks up the latest releases of a repository, excluding pre-releases, along with the commit
+	/// of each.
+	///
+	/// # Arguments
+	///
+	/// * `number` - the number of releases to look up
+	/// * `repo` - the url of the repository
+	pub async fn get_latest_n_releases(&self, number: usize, repo: &Url) -> Result<Vec<Release>> {
+		let mut releases: Vec<Release> = self
+			.get_latest_releases(repo)
 			.await?
 			.into_iter()
 			.filter(|r| !r.prerelease)
@@ -175,16 +445,14 @@ impl GitHub {
 			.collect();
 		// Additional lookup for commit sha
 		for release in releases.iter_mut() {
-			let response = client
-				.get(format!(
-					"https://api.github.com/repos/{}/{}/git/ref/tags/{}",
-					Self::org(repo)?,
-					Self::name(repo)?,
+			let value: serde_json::Value = self
+				.get(&format!(
+					"repos/{}/{}/git/ref/tags/{}",
+					GitHub.org(repo)?,
+					GitHub.name(repo)?,
 					&release.tag_name
 				))
-				.send()
 				.await?;
-			let value = response.json::<serde_json::Value>().await?;
 			let commit = value
 				.get("object")
 				.and_then(|v| v.get("sha"))
@@ -196,7 +464,105 @@ impl GitHub {
 		Ok(releases)
 	}
 
-	fn org(repo: &Url) -> Result<&str> {
+	// Sends a request to the api, using a cached response when still valid.
+	async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+		Ok(self.get_page(path).await?.0)
+	}
+
+	// Sends a request to the api, using a cached response when still valid, along with the path of
+	// the next page of results advertised by the `Link` header, if any. A stale cached response is
+	// used when the rate limit has been exceeded.
+	async fn get_page<T: DeserializeOwned>(&self, path: &str) -> Result<(T, Option<String>)> {
+		static APP_USER_AGENT: &str =
+			concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+		// Responses are cached per request, and therefore per repository and page, along with
+		// the path of the next page
+		let cached = self
+			.cache
+			.as_ref()
+			.map(|c| c.join(format!("{}.json", path.replace(['/', '?', '&', '='], "_"))));
+		let read = |stale: bool| -> Option<(T, Option<String>)> {
+			let cached = cached.as_ref()?;
+			let age = cached.metadata().ok()?.modified().ok()?.elapsed().unwrap_or_default();
+			if !stale && age > Self::TTL {
+				return None;
+			}
+			let next = fs::read_to_string(cached.with_extension("next")).ok();
+			Some((serde_json::from_slice(&fs::read(cached).ok()?).ok()?, next))
+		};
+		if let Some(response) = read(false) {
+			return Ok(response);
+		}
+
+		let client = reqwest::ClientBuilder::new().user_agent(APP_USER_AGENT).build()?;
+		let mut request = client.get(format!("{}/{path}", self.api));
+		if let Some(token) = &self.token {
+			request = request.bearer_auth(token);
+		}
+		let response = request.send().await?;
+		if let Some(reset) = rate_limit(&response) {
+			return match read(true) {
+				Some(response) => Ok(response),
+				None => Err(Error::RateLimited { reset }.into()),
+			};
+		}
+		let next = response
+			.headers()
+			.get(reqwest::header::LINK)
+			.and_then(|v| v.to_str().ok())
+			.and_then(next_page)
+			.and_then(|url| url.strip_prefix(&format!("{}/", self.api)))
+			.map(String::from);
+		let body = response.error_for_status()?.bytes().await?;
+		let value = serde_json::from_slice(&body)?;
+		if let Some(cached) = cached {
+			if let Some(parent) = cached.parent() {
+				fs::create_dir_all(parent)?;
+			}
+			fs::write(&cached, &body)?;
+			match &next {
+				Some(next) => fs::write(cached.with_extension("next"), next)?,
+				None => {
+					let _ = fs::remove_file(cached.with_extension("next"));
+				},
+			}
+		}
+		Ok((value, next))
+	}
+}
+
+// The url of the next page of results, from the value of a `Link` header (e.g.
+// `<https://api.github.com/...&page=2>; rel="next", <https://api.github.com/...&page=5>;
+// rel="last"`).
+fn next_page(link: &str) -> Option<&str> {
+	link.split(',').find_map(|link| {
+		let (url, params) = link.split_once(';')?;
+		params
+			.split(';')
+			.any(|p| p.trim() == r#"rel="next""#)
+			.then(|| url.trim().trim_start_matches('<').trim_end_matches('>'))
+	})
+}
+
+// Determines whether a response indicates that the rate limit has been exceeded, returning the
+// time at which it resets (in seconds since the unix epoch).
+fn rate_limit(response: &reqwest::Response) -> Option<u64> {
+	let header = |name: &str| -> Option<u64> {
+		response
+			.headers()
+			.get(name)
+			.and_then(|v| v.to_str().ok())
+			.and_then(|v| v.parse().ok())
+	};
+	let limited =
+		matches!(response.status(), StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS) &&
+			header("x-ratelimit-remaining") == Some(0);
+	limited.then(|| header("x-ratelimit-reset").unwrap_or_default())
+}
+
+impl GitProvider for GitHub {
+	fn org<'a>(&self, repo: &'a Url) -> Result<&'a str> {
 		let path_segments = repo
 			.path_segments()
 			.map(|c| c.collect::<Vec<_>>())
@@ -206,22 +572,225 @@ impl GitHub {
 		))?)
 	}
 
-	pub(crate) fn name(repo: &Url) -> Result<&str> {
+	fn name<'a>(&self, repo: &'a Url) -> Result<&'a str> {
 		let path_segments = repo
 			.path_segments()
 			.map(|c| c.collect::<Vec<_>>())
 			.expect("repository must have path segments");
-		Ok(path_segments
+		let name = path_segments
 			.get(1)
-			.ok_or(Error::Git("the repository name is missing from the github url".to_string()))?)
+			.ok_or(Error::Git("the repository name is missing from the github url".to_string()))?;
+		Ok(name.strip_suffix(".git").unwrap_or(name))
 	}
 
-	pub(crate) fn release(repo: &Url, tag: &str, artifact: &str) -> String {
-		format!("{}/releases/download/{tag}/{artifact}", repo.as_str())
+	fn releases<'a>(&'a self, github: &'a GitHubClient, repo: &'a Url) -> Releases<'a> {
+		Box::pin(github.get_latest_releases(repo))
 	}
-	pub(crate) fn convert_to_ssh_url(url: &Url) -> String {
-		format!("git@{}:{}.git", url.host_str().unwrap_or(Self::GITHUB), &url.path()[1..])
+}
+
+/// The releases of a repository, as looked up from its provider.
+pub type Releases<'a> = Pin<Box<dyn Future<Output = Result<Vec<Release>>> + Send + 'a>>;
+
+/// A provider hosting git repositories, determining how a repository is cloned, named and its
+/// releases looked up.
+pub trait GitProvider: Sync {
+	/// The url used to clone a repository, without any `<provider>+` scheme prefix.
+	///
+	/// # Arguments
+	///
+	/// * `repo` - the url of the repository
+	fn clone_url(&self, repo: &Url) -> Result<Url> {
+		match repo.scheme().split_once('+') {
+			Some((_, scheme)) => {
+				let url = &repo.as_str()[repo.scheme().len() - scheme.len()..];
+				Ok(Url::parse(url).map_err(Error::from)?)
+			},
+			None => Ok(repo.clone()),
+		}
+	}
+
+	/// The ssh url of a repository, used when cloning over https fails.
+	///
+	/// # Arguments
+	///
+	/// * `repo` - the url of the repository
+	fn ssh_url(&self, repo: &Url) -> Option<String> {
+		let host = repo.host_str()?;
+		let path = repo.path().trim_matches('/');
+		Some(format!("git@{host}:{}.git", path.strip_suffix(".git").unwrap_or(path)))
+	}
+
+	/// The organization (or user/group) owning a repository.
+	///
+	/// # Arguments
+	///
+	/// * `repo` - the url of the repository
+	fn org<'a>(&self, repo: &'a Url) -> Result<&'a str> {
+		let path = repo.path().trim_matches('/');
+		path.rsplit_once('/')
+			.map(|(org, _)| org)
+			.ok_or(Error::Git(format!("the organization (or user) is missing from {repo}")).into())
 	}
+
+	/// The name of a repository, without any `.git` suffix.
+	///
+	/// # Arguments
+	///
+	/// * `repo` - the url of the repository
+	fn name<'a>(&self, repo: &'a Url) -> Result<&'a str> {
+		let name = repo
+			.path_segments()
+			.and_then(|mut s| s.rfind(|s| !s.is_empty()))
+			.ok_or(Error::Git(format!("the repository name is missing from {repo}")))?;
+		Ok(name.strip_suffix(".git").unwrap_or(name))
+	}
+
+	/// Looks up the releases of a repository, newest first.
+	///
+	/// # Arguments
+	///
+	/// * `github` - the client used to look up releases of repositories hosted on GitHub
+	/// * `repo` - the url of the repository
+	fn releases<'a>(&'a self, github: &'a GitHubClient, repo: &'a Url) -> Releases<'a>;
+}
+
+/// A repository hosted on GitLab, including self-hosted instances.
+pub struct GitLab;
+impl GitLab {
+	const GITLAB: &'static str = "gitlab.com";
+	const PREFIX: &'static str = "gitlab";
+}
+
+impl GitProvider for GitLab {
+	fn releases<'a>(&'a self, _github: &'a GitHubClient, repo: &'a Url) -> Releases<'a> {
+		#[derive(serde::Deserialize)]
+		struct GitLabRelease {
+			tag_name: String,
+			name: Option<String>,
+			#[serde(default)]
+			upcoming_release: bool,
+			commit: Option<GitLabCommit>,
+			#[serde(default)]
+			assets: GitLabAssets,
+		}
+		#[derive(Default, serde::Deserialize)]
+		struct GitLabAssets {
+			#[serde(default)]
+			links: Vec<Asset>,
+		}
+		#[derive(serde::Deserialize)]
+		struct GitLabCommit {
+			id: String,
+		}
+
+		Box::pin(async move {
+			let url = self.clone_url(repo)?;
+			let path = format!("{}/{}", self.org(&url)?, self.name(&url)?);
+			let segments: Vec<_> = path.split('/').collect();
+			// An instance served from a subpath (e.g. `https://example.com/gitlab`) cannot be
+			// distinguished from a group, so each possible subpath is tried in turn
+			let mut index = 0;
+			let response = loop {
+				let instance = instance(&url, &segments[..index].join("/"));
+				// Projects are identified by their url-encoded path, which may include subgroups
+				let project = segments[index..].join("%2F");
+				match api(&instance, &format!("api/v4/projects/{project}/releases")).await {
+					Err(e) if index + 2 < segments.len() && is_not_found(&e) => index += 1,
+					response => break response?,
+				}
+			};
+			let releases = response.json::<Vec<GitLabRelease>>().await?;
+			Ok(releases
+				.into_iter()
+				.map(|r| Release {
+					name: r.name.unwrap_or_else(|| r.tag_name.clone()),
+					tag_name: r.tag_name,
+					prerelease: r.upcoming_release,
+					commit: r.commit.map(|c| c.id),
+					assets: r.assets.links,
+				})
+				.collect())
+		})
+	}
+}
+
+/// A repository hosted on Gitea (or Forgejo), including self-hosted instances.
+pub struct Gitea;
+impl Gitea {
+	const CODEBERG: &'static str = "codeberg.org";
+	const PREFIX: &'static str = "gitea";
+}
+
+impl GitProvider for Gitea {
+	fn releases<'a>(&'a self, _github: &'a GitHubClient, repo: &'a Url) -> Releases<'a> {
+		Box::pin(async move {
+			let url = self.clone_url(repo)?;
+			// Repositories are always owned by a single user or organization, so any preceding
+			// segments are the subpath from which the instance is served
+			let org = self.org(&url)?;
+			let (subpath, owner) = org.rsplit_once('/').unwrap_or(("", org));
+			let path = format!("api/v1/repos/{owner}/{}/releases", self.name(&url)?);
+			Ok(api(&instance(&url, subpath), &path).await?.json::<Vec<Release>>().await?)
+		})
+	}
+}
+
+/// A repository on the local filesystem (e.g. `file:///srv/git/pop-node.git`), whose tags are
+/// considered its releases.
+pub struct LocalRepository;
+
+impl GitProvider for LocalRepository {
+	fn ssh_url(&self, _repo: &Url) -> Option<String> {
+		None
+	}
+
+	fn releases<'a>(&'a self, _github: &'a GitHubClient, repo: &'a Url) -> Releases<'a> {
+		Box::pin(async move {
+			let path = repo
+				.to_file_path()
+				.map_err(|_| Error::Git(format!("{repo} is not a local repository")))?;
+			let repository = Repository::open(path)?;
+			let mut releases = Vec::new();
+			for tag in repository.tag_names(None)?.iter().flatten() {
+				let commit =
+					repository.revparse_single(&format!("refs/tags/{tag}"))?.peel_to_commit()?;
+				let release = Release {
+					tag_name: tag.to_string(),
+					name: tag.to_string(),
+					prerelease: tag.contains('-'),
+					commit: Some(commit.id().to_string()),
+					assets: Vec::new(),
+				};
+				releases.push((commit.time().seconds(), release));
+			}
+			// Newest first, consistent with the release listings of hosted providers, with tags of
+			// the same commit time ordered by name
+			releases.sort_by(|(a, x), (b, y)| b.cmp(a).then_with(|| y.tag_name.cmp(&x.tag_name)));
+			Ok(releases.into_iter().map(|(_, r)| r).collect())
+		})
+	}
+}
+
+// The url of the instance hosting a repository, served from the provided subpath.
+fn instance(repo: &Url, subpath: &str) -> Url {
+	let mut url = repo.clone();
+	url.set_path(subpath);
+	url
+}
+
+// Sends a request to the api of an instance, at a path relative to the url of the instance.
+async fn api(instance: &Url, path: &str) -> Result<reqwest::Response> {
+	static APP_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+	let mut url = instance.clone();
+	url.set_path(&format!("{}/{path}", instance.path().trim_end_matches('/')));
+	let client = reqwest::ClientBuilder::new().user_agent(APP_USER_AGENT).build()?;
+	Ok(client.get(url).send().await?.error_for_status()?)
+}
+
+// Whether a request failed as the requested resource was not found.
+pub(crate) fn is_not_found(error: &anyhow::Error) -> bool {
+	error.downcast_ref::<reqwest::Error>().and_then(|e| e.status()) == Some(StatusCode::NOT_FOUND)
 }
 
 #[derive(serde::Deserialize)]
@@ -230,34 +799,329 @@ pub struct Release {
 	pub name: String,
 	pub prerelease: bool,
 	pub commit: Option<String>,
+	/// The artifacts published with the release.
+	#[serde(default)]
+	pub assets: Vec<Asset>,
+}
+
+/// An artifact published with a release.
+#[derive(serde::Deserialize)]
+pub struct Asset {
+	/// The name of the artifact.
+	pub name: String,
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::utils::testing::{serve, serve_with_headers};
+	use git2::Signature;
+	use std::time::SystemTime;
+
+	#[test]
+	fn test_resolve_commit() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let [tagged, branched, latest] = origin(temp_dir.path())?;
+		let url = Url::from_directory_path(temp_dir.path()).unwrap();
+
+		assert_eq!(Git::resolve_commit(&url, Some("v1.0"))?, tagged.to_string());
+		assert_eq!(Git::resolve_commit(&url, Some("feature"))?, branched.to_string());
+		assert_eq!(Git::resolve_commit(&url, None)?, latest.to_string());
+		let abbreviated = &branched.to_string()[..7];
+		assert_eq!(Git::resolve_commit(&url, Some(abbreviated))?, abbreviated);
+		assert!(Git::resolve_commit(&url, Some("missing")).is_err());
+		// Full commits are returned as is, without connecting to the remote
+		let commit = "A".repeat(40);
+		let missing = Url::from_directory_path(temp_dir.path().join("missing")).unwrap();
+		assert_eq!(Git::resolve_commit(&missing, Some(&commit))?, commit.to_lowercase());
+		Ok(())
+	}
+
+	#[test]
+	fn test_is_commit() {
+		assert!(Git::is_commit("3a4b5c6d7e8f90123456789abcdef0123456789a"));
+		assert!(Git::is_commit("3a4b5c6"));
+		assert!(!Git::is_commit("3a4b5c"));
+		assert!(!Git::is_commit("main"));
+		assert!(!Git::is_commit(&"g".repeat(40)));
+		assert!(!Git::is_commit(&"a".repeat(41)));
+		assert!(Git::is_full_commit("3a4b5c6d7e8f90123456789abcdef0123456789a"));
+		assert!(!Git::is_full_commit("3a4b5c6"));
+	}
+
+	// Creates a repository with an annotated tag, a branch and then a further commit on the
+	// default branch, to be used as a remote. Returns the commits, in order.
+	fn origin(path: &Path) -> Result<[Oid; 3]> {
+		let repo = Repository::init(path)?;
+		let signature = Signature::now("pop", "pop@r0gue.io")?;
+		let commit = |message: &str| -> Result<Oid> {
+			fs::write(path.join("file"), message)?;
+			let mut index = repo.index()?;
+			index.add_path(Path::new("file"))?;
+			let tree = repo.find_tree(index.write_tree()?)?;
+			let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+			let parents: Vec<_> = parent.iter().collect();
+			Ok(repo.commit(Some("HEAD"), &signature, &signature, message, &tree, &parents)?)
+		};
+		let tagged = commit("tagged")?;
+		repo.tag("v1.0", &repo.find_object(tagged, None)?, &signature, "v1.0", false)?;
+		let branched = commit("branched")?;
+		repo.branch("feature", &repo.find_commit(branched)?, false)?;
+		Ok([tagged, branched, commit("latest")?])
+	}
+
+	#[test]
+	fn test_fetch_and_checkout() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let path = temp_dir.path().join("origin");
+		let [tagged, branched, latest] = origin(&path)?;
+
+		let working_dir = temp_dir.path().join("working");
+		Repository::clone(Url::from_directory_path(&path).unwrap().as_str(), &working_dir)?;
+		let head = || -> Result<Oid> {
+			Ok(Repository::open(&working_dir)?.head()?.peel_to_commit()?.id())
+		};
+		Git::fetch_and_checkout(&working_dir, Some("v1.0"))?;
+		assert_eq!(head()?, tagged);
+		assert_eq!(fs::read_to_string(working_dir.join("file"))?, "tagged");
+		Git::fetch_and_checkout(&working_dir, Some("feature"))?;
+		assert_eq!(head()?, branched);
+		Git::fetch_and_checkout(&working_dir, None)?;
+		assert_eq!(head()?, latest);
+		Git::fetch_and_checkout(&working_dir, Some(&tagged.to_string()))?;
+		assert_eq!(head()?, tagged);
+		Git::fetch_and_checkout(&working_dir, Some(&branched.to_string()[..7]))?;
+		assert_eq!(head()?, branched);
+		assert!(Git::fetch_and_checkout(&working_dir, Some("missing")).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn test_clone_commit() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let path = temp_dir.path().join("origin");
+		let [_, branched, _] = origin(&path)?;
+
+		let working_dir = temp_dir.path().join("working");
+		let url = Url::from_directory_path(&path).unwrap();
+		Git::clone(&url, &working_dir, Some(&branched.to_string()))?;
+		assert_eq!(Repository::open(&working_dir)?.head()?.peel_to_commit()?.id(), branched);
+		assert_eq!(fs::read_to_string(working_dir.join("file"))?, "branched");
+		// Abbreviated commits are resolved once fetched
+		let working_dir = temp_dir.path().join("abbreviated");
+		Git::clone(&url, &working_dir, Some(&branched.to_string()[..7]))?;
+		assert_eq!(Repository::open(&working_dir)?.head()?.peel_to_commit()?.id(), branched);
+		let working_dir = temp_dir.path().join("unknown");
+		assert!(Git::clone(&url, &working_dir, Some("3a4b5c6")).is_err());
+		Ok(())
+	}
 
 	#[test]
 	fn test_convert_to_ssh_url() {
 		assert_eq!(
-			GitHub::convert_to_ssh_url(
+			GitHub.ssh_url(
 				&Url::parse("https://github.com/r0gue-io/base-parachain")
 					.expect("valid repository url")
 			),
-			"git@github.com:r0gue-io/base-parachain.git"
+			Some("git@github.com:r0gue-io/base-parachain.git".into())
 		);
 		assert_eq!(
-			GitHub::convert_to_ssh_url(
+			GitHub.ssh_url(
 				&Url::parse("https://github.com/paritytech/substrate-contracts-node")
 					.expect("valid repository url")
 			),
-			"git@github.com:paritytech/substrate-contracts-node.git"
+			Some("git@github.com:paritytech/substrate-contracts-node.git".into())
 		);
 		assert_eq!(
-			GitHub::convert_to_ssh_url(
+			GitHub.ssh_url(
 				&Url::parse("https://github.com/paritytech/frontier-parachain-template")
 					.expect("valid repository url")
 			),
-			"git@github.com:paritytech/frontier-parachain-template.git"
+			Some("git@github.com:paritytech/frontier-parachain-template.git".into())
+		);
+	}
+
+	#[tokio::test]
+	async fn test_github_client_uses_cached_response() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let cache = temp_dir.path().join(".github");
+		fs::create_dir_all(&cache)?;
+		fs::write(
+			cache.join("repos_r0gue-io_pop-node_releases_per_page_100.json"),
+			r#"[{"tag_name":"v1.0.0","name":"v1.0.0","prerelease":false}]"#,
+		)?;
+
+		let repo = Url::parse("https://github.com/r0gue-io/pop-node")?;
+		let client = GitHubClient::new().with_token(None).with_cache(temp_dir.path());
+		let releases = client.get_latest_releases(&repo).await?;
+		assert_eq!(releases.len(), 1);
+		assert_eq!(releases[0].tag_name, "v1.0.0");
+		assert!(Error::RateLimited { reset: 0 }.to_string().contains("resetting in 0 seconds"));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_get_latest_n_releases() -> Result<()> {
+		let url = serve(|path, _| match path {
+			"/repos/paritytech/polkadot-sdk/releases?per_page=100" =>
+				(200, include_bytes!("../../../../tests/github/releases.json").to_vec()),
+			path => match path.strip_prefix("/repos/paritytech/polkadot-sdk/git/ref/tags/") {
+				Some(tag) => (200, format!(r#"{{"object":{{"sha":"{tag}-sha"}}}}"#).into_bytes()),
+				None => (404, Vec::new()),
+			},
+		});
+		let repo = Url::parse("https://github.com/paritytech/polkadot-sdk")?;
+		let client = GitHubClient::new().with_token(None).with_api(&url);
+
+		let releases = client.get_latest_n_releases(2, &repo).await?;
+		let releases: Vec<_> =
+			releases.iter().map(|r| (r.tag_name.as_str(), r.commit.as_deref())).collect();
+		assert_eq!(
+			releases,
+			[
+				("polkadot-v1.10.0", Some("polkadot-v1.10.0-sha")),
+				("polkadot-v1.9.0", Some("polkadot-v1.9.0-sha"))
+			]
 		);
+		Ok(())
+	}
+
+	#[test]
+	fn test_next_page() {
+		let link = r#"<https://api.github.com/repositories/1/releases?per_page=100&page=2>; rel="next", <https://api.github.com/repositories/1/releases?per_page=100&page=5>; rel="last""#;
+		assert_eq!(
+			next_page(link),
+			Some("https://api.github.com/repositories/1/releases?per_page=100&page=2")
+		);
+		let link = r#"<https://api.github.com/repositories/1/releases?per_page=100&page=4>; rel="prev""#;
+		assert_eq!(next_page(link), None);
+	}
+
+	#[tokio::test]
+	async fn test_rate_limited() -> Result<()> {
+		let url = serve_with_headers(|_, _| {
+			let headers =
+				vec![("x-ratelimit-remaining", "0".into()), ("x-ratelimit-reset", "60".into())];
+			(403, headers, Vec::new())
+		});
+		let repo = Url::parse("https://github.com/r0gue-io/pop-node")?;
+		let temp_dir = tempfile::tempdir()?;
+		let client =
+			GitHubClient::new().with_token(None).with_api(&url).with_cache(temp_dir.path());
+		let error = client.get_latest_releases(&repo).await.err().expect("rate limited");
+		assert!(matches!(error.downcast_ref::<Error>(), Some(Error::RateLimited { reset: 60 })));
+
+		// A stale cached response is used instead, when available
+		let cached =
+			temp_dir.path().join(".github/repos_r0gue-io_pop-node_releases_per_page_100.json");
+		fs::create_dir_all(cached.parent().unwrap())?;
+		fs::write(&cached, r#"[{"tag_name":"v1.0.0","name":"v1.0.0","prerelease":false}]"#)?;
+		fs::File::options()
+			.write(true)
+			.open(&cached)?
+			.set_modified(SystemTime::now() - GitHubClient::TTL * 2)?;
+		assert_eq!(client.get_latest_releases(&repo).await?[0].tag_name, "v1.0.0");
+		Ok(())
+	}
+
+	#[test]
+	fn test_provider() -> Result<()> {
+		let name = |url: &str| -> Result<String> {
+			let url = Url::parse(url)?;
+			Ok(Git::provider(&url).name(&url)?.to_string())
+		};
+		assert_eq!(name("https://github.com/r0gue-io/pop-node.git")?, "pop-node");
+		assert_eq!(name("https://gitlab.com/group/subgroup/pop-node/")?, "pop-node");
+		assert_eq!(name("gitlab+https://git.example.com/group/pop-node")?, "pop-node");
+		assert_eq!(name("file:///srv/git/pop-node.git")?, "pop-node");
+
+		let url = Url::parse("gitlab+https://git.example.com/group/subgroup/pop-node.git")?;
+		let provider = Git::provider(&url);
+		assert_eq!(provider.org(&url)?, "group/subgroup");
+		assert_eq!(
+			provider.clone_url(&url)?.as_str(),
+			"https://git.example.com/group/subgroup/pop-node.git"
+		);
+		assert_eq!(
+			provider.ssh_url(&provider.clone_url(&url)?),
+			Some("git@git.example.com:group/subgroup/pop-node.git".into())
+		);
+		let url = Url::parse("file:///srv/git/pop-node.git")?;
+		assert_eq!(Git::provider(&url).ssh_url(&url), None);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_github_releases_use_client() -> Result<()> {
+		let url = serve(|path, _| match path {
+			"/repos/r0gue-io/pop-node/releases?per_page=100" =>
+				(200, br#"[{"tag_name":"v1.0.0","name":"v1.0.0","prerelease":false}]"#.to_vec()),
+			_ => (404, Vec::new()),
+		});
+		let temp_dir = tempfile::tempdir()?;
+		let github =
+			GitHubClient::new().with_token(None).with_api(&url).with_cache(temp_dir.path());
+		let repo = Url::parse("https://github.com/r0gue-io/pop-node")?;
+		let releases = Git::provider(&repo).releases(&github, &repo).await?;
+		assert_eq!(releases[0].tag_name, "v1.0.0");
+		// The response is cached by the client
+		assert!(temp_dir
+			.path()
+			.join(".github/repos_r0gue-io_pop-node_releases_per_page_100.json")
+			.exists());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_releases_of_instance_served_from_subpath() -> Result<()> {
+		const RELEASES: &str = r#"[{"tag_name":"v1.0.0","name":"v1.0.0","prerelease":false}]"#;
+		let url = serve(|path, _| match path {
+			"/git/api/v4/projects/group%2Fsubgroup%2Fpop-node/releases" |
+			"/git/api/v1/repos/org/pop-node/releases" => (200, RELEASES.into()),
+			_ => (404, Vec::new()),
+		});
+		let github = GitHubClient::new().with_token(None);
+		for repo in [
+			format!("gitlab+{url}/git/group/subgroup/pop-node.git"),
+			format!("gitea+{url}/git/org/pop-node"),
+		] {
+			let repo = Url::parse(&repo)?;
+			let releases = Git::provider(&repo).releases(&github, &repo).await?;
+			assert_eq!(releases[0].tag_name, "v1.0.0");
+		}
+		// Instances without a subpath are unaffected
+		let url = serve(|path, _| match path {
+			"/api/v4/projects/group%2Fpop-node/releases" => (200, RELEASES.into()),
+			_ => (404, Vec::new()),
+		});
+		let repo = Url::parse(&format!("gitlab+{url}/group/pop-node"))?;
+		assert_eq!(Git::provider(&repo).releases(&github, &repo).await?.len(), 1);
+		let repo = Url::parse(&format!("gitlab+{url}/missing/pop-node"))?;
+		assert!(is_not_found(&Git::provider(&repo).releases(&github, &repo).await.err().unwrap()));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_local_releases() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let [tagged, _, latest] = origin(temp_dir.path())?;
+		let repo = Repository::open(temp_dir.path())?;
+		repo.tag_lightweight("v2.0-rc1", &repo.find_object(latest, None)?, false)?;
+
+		let url = Url::from_directory_path(temp_dir.path()).unwrap();
+		let github = GitHubClient::new().with_token(None);
+		let releases = Git::provider(&url).releases(&github, &url).await?;
+		let releases: Vec<_> = releases
+			.iter()
+			.map(|r| (r.tag_name.as_str(), r.prerelease, r.commit.clone()))
+			.collect();
+		assert_eq!(
+			releases,
+			[
+				("v2.0-rc1", true, Some(latest.to_string())),
+				("v1.0", false, Some(tagged.to_string()))
+			]
+		);
+		Ok(())
 	}
 }
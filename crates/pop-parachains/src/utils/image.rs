@@ -0,0 +1,387 @@
+// SPDX-License-Identifier: GPL-3.0
+use crate::errors::Error;
+use flate2::read::GzDecoder;
+use std::{
+	collections::HashMap,
+	fs::{set_permissions, File},
+	io::{BufRead, BufReader, Read, Seek, SeekFrom},
+	os::unix::fs::PermissionsExt,
+	path::{Component, Path, PathBuf},
+};
+use tar::{Archive, EntryType};
+
+/// The maximum number of symbolic links followed when locating a binary within an image.
+const MAX_LINKS: usize = 8;
+
+/// A local OCI/Docker image archive, as output by `docker save`, from which binaries can be
+/// extracted.
+pub(crate) struct ImageArchive {
+	/// The location of the archive.
+	path: PathBuf,
+	/// The location and size of each file within the archive.
+	entries: HashMap<String, (u64, u64)>,
+}
+
+// The result of searching the image layers for a path.
+enum Lookup {
+	/// The binary was extracted.
+	Extracted,
+	/// The path is a symbolic link to another path within the image.
+	Link(String),
+}
+
+impl ImageArchive {
+	/// Opens an image archive, indexing its contents.
+	///
+	/// # Arguments
+	///
+	/// * `path` - the location of the archive
+	pub(crate) fn open(path: &Path) -> Result<Self, Error> {
+		// The archive itself is uncompressed, so its files can be read directly once located
+		let mut entries = HashMap::new();
+		for entry in Archive::new(File::open(path)?).entries()? {
+			let entry = entry?;
+			let name = normalize(&entry.path()?);
+			entries.insert(name, (entry.raw_file_position(), entry.size()));
+		}
+		Ok(Self { path: path.to_path_buf(), entries })
+	}
+
+	/// Extracts a binary from the image, searching the image layers from the top.
+	///
+	/// # Arguments
+	///
+	/// * `binary` - the path of the binary within the image (e.g. `/usr/bin/polkadot`)
+	/// * `dest` - the destination of the extracted binary
+	pub(crate) fn extract(&self, binary: &str, dest: &Path) -> Result<(), Error> {
+		let layers = self.layers()?;
+		let mut target = normalize(Path::new(binary));
+		for _ in 0..=MAX_LINKS {
+			match self.lookup(&layers, &target, dest)? {
+				Some(Lookup::Extracted) => {
+					let mut perms = dest.metadata()?.permissions();
+					perms.set_mode(0o755);
+					set_permissions(dest, perms)?;
+					return Ok(());
+				},
+				Some(Lookup::Link(link)) => target = link,
+				None => break,
+			}
+		}
+		Err(Error::MissingBinary(format!("{binary} not found within {}", self.path.display())))
+	}
+
+	// Searches the layers, from the top, for the provided path.
+	fn lookup(
+		&self,
+		layers: &[String],
+		target: &str,
+		dest: &Path,
+	) -> Result<Option<Lookup>, Error> {
+		let whiteouts = whiteouts(target);
+		for layer in layers.iter().rev() {
+			let mut layer = Archive::new(self.layer(layer)?);
+			let mut deleted = false;
+			for entry in layer.entries()? {
+				let mut entry = entry?;
+				let path = normalize(&entry.path()?);
+				if whiteouts.contains(&path) {
+					deleted = true;
+					continue;
+				}
+				if path != target {
+					continue;
+				}
+				return match entry.header().entry_type() {
+					EntryType::Regular | EntryType::Continuous => {
+						entry.unpack(dest)?;
+						Ok(Some(Lookup::Extracted))
+					},
+					EntryType::Symlink | EntryType::Link => {
+						let link = entry
+							.link_name()?
+							.ok_or(Error::Archive(format!("the link at {target} has no target")))?;
+						// Absolute links and hard links are relative to the root of the image
+						let link = match entry.header().entry_type() {
+							EntryType::Symlink if !link.is_absolute() => Path::new(target)
+								.parent()
+								.map(|p| p.join(&link))
+								.unwrap_or(link.into_owned()),
+							_ => link.into_owned(),
+						};
+						Ok(Some(Lookup::Link(normalize(&link))))
+					},
+					_ => Err(Error::Archive(format!("{target} is not a file"))),
+				};
+			}
+			// The path, or one of its ancestors, was deleted by this layer, hiding any lower layers
+			if deleted {
+				return Ok(None);
+			}
+		}
+		Ok(None)
+	}
+
+	// Determines the layers of the image, from the bottom.
+	fn layers(&self) -> Result<Vec<String>, Error> {
+		if self.entries.contains_key("manifest.json") {
+			// Docker archive, as output by `docker save`
+			let manifest = self.json("manifest.json")?;
+			manifest
+				.get(0)
+				.and_then(|m| m.get("Layers"))
+				.and_then(|l| l.as_array())
+				.map(|layers| {
+					layers
+						.iter()
+						.filter_map(|l| l.as_str())
+						.map(|l| normalize(Path::new(l)))
+						.collect()
+				})
+				.ok_or(Error::Archive("expected `Layers` within the image manifest".into()))
+		} else if self.entries.contains_key("index.json") {
+			// OCI image layout
+			let index = self.json("index.json")?;
+			let digest = index
+				.get("manifests")
+				.and_then(|m| m.get(0))
+				.and_then(|m| m.get("digest"))
+				.and_then(|d| d.as_str())
+				.ok_or(Error::Archive("expected a manifest within the image index".into()))?;
+			let manifest = self.json(&blob(digest)?)?;
+			manifest
+				.get("layers")
+				.and_then(|l| l.as_array())
+				.ok_or(Error::Archive("expected `layers` within the image manifest".into()))?
+				.iter()
+				.map(|l| {
+					l.get("digest")
+						.and_then(|d| d.as_str())
+						.ok_or(Error::Archive("expected a digest for each image layer".into()))
+						.and_then(blob)
+				})
+				.collect()
+		} else {
+			Err(Error::Archive(format!(
+				"{} is not an image archive: expected a `manifest.json` or `index.json`",
+				self.path.display()
+			)))
+		}
+	}
+
+	// Opens a layer of the image, which may be compressed.
+	fn layer(&self, layer: &str) -> Result<Box<dyn Read>, Error> {
+		let mut reader = BufReader::new(self.read(layer)?);
+		let compressed = reader.fill_buf()?.starts_with(&[0x1f, 0x8b]);
+		Ok(match compressed {
+			true => Box::new(GzDecoder::new(reader)),
+			false => Box::new(reader),
+		})
+	}
+
+	// Reads and parses a JSON file within the archive.
+	fn json(&self, name: &str) -> Result<serde_json::Value, Error> {
+		let mut contents = Vec::new();
+		self.read(name)?.read_to_end(&mut contents)?;
+		serde_json::from_slice(&contents)
+			.map_err(|e| Error::Archive(format!("unable to parse {name}: {e}")))
+	}
+
+	// Reads a file within the archive.
+	fn read(&self, name: &str) -> Result<impl Read, Error> {
+		let (position, size) = self
+			.entries
+			.get(name)
+			.ok_or(Error::Archive(format!("{name} not found within {}", self.path.display())))?;
+		let mut file = File::open(&self.path)?;
+		file.seek(SeekFrom::Start(*position))?;
+		Ok(file.take(*size))
+	}
+}
+
+// The location of a blob within an OCI image layout, from its digest (e.g. `sha256:<hex>`).
+fn blob(digest: &str) -> Result<String, Error> {
+	let (algorithm, hash) = digest
+		.split_once(':')
+		.ok_or(Error::Archive(format!("invalid digest: {digest}")))?;
+	Ok(format!("blobs/{algorithm}/{hash}"))
+}
+
+// The whiteout entries which, when within a layer, delete the provided path from the layers below:
+// a whiteout of the path or any of its ancestors (e.g. `usr/.wh.bin`), or an opaque whiteout of
+// any directory containing it (e.g. `usr/bin/.wh..wh..opq`).
+fn whiteouts(path: &str) -> Vec<String> {
+	let mut whiteouts = Vec::new();
+	let mut parent = String::new();
+	for name in path.split('/') {
+		whiteouts.push(format!("{parent}.wh..wh..opq"));
+		whiteouts.push(format!("{parent}.wh.{name}"));
+		parent = format!("{parent}{name}/");
+	}
+	whiteouts
+}
+
+// Normalizes a path within an archive, relative to its root (e.g. `./usr/bin/polkadot` becomes
+// `usr/bin/polkadot`).
+fn normalize(path: &Path) -> String {
+	let mut components = Vec::new();
+	for component in path.components() {
+		match component {
+			Component::Normal(c) => components.push(c.to_string_lossy()),
+			Component::ParentDir => {
+				components.pop();
+			},
+			_ => {},
+		}
+	}
+	components.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+	use flate2::{write::GzEncoder, Compression};
+	use std::fs::read;
+	use tar::{Builder, Header};
+
+	// Builds a layer containing the provided files, where a `None` contents denotes a symbolic
+	// link to the path specified.
+	fn layer(files: &[(&str, &[u8], Option<&str>)]) -> Result<Vec<u8>> {
+		let mut builder = Builder::new(Vec::new());
+		for (path, contents, link) in files {
+			let mut header = Header::new_gnu();
+			header.set_mode(0o644);
+			match link {
+				Some(link) => {
+					header.set_entry_type(EntryType::Symlink);
+					header.set_size(0);
+					builder.append_link(&mut header, path, link)?;
+				},
+				None => {
+					header.set_size(contents.len() as u64);
+					builder.append_data(&mut header, path, *contents)?;
+				},
+			}
+		}
+		Ok(builder.into_inner()?)
+	}
+
+	fn archive(path: &Path, files: &[(&str, Vec<u8>)]) -> Result<()> {
+		let mut builder = Builder::new(File::create(path)?);
+		for (name, contents) in files {
+			let mut header = Header::new_gnu();
+			header.set_size(contents.len() as u64);
+			header.set_mode(0o644);
+			builder.append_data(&mut header, name, contents.as_slice())?;
+		}
+		builder.finish()?;
+		Ok(())
+	}
+
+	#[test]
+	fn test_extract_from_docker_archive() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let path = temp_dir.path().join("image.tar");
+		let base = layer(&[("usr/bin/node", b"old", None), ("usr/bin/removed", b"", None)])?;
+		let top = layer(&[
+			("./usr/bin/node", b"new", None),
+			("usr/local/bin/node", b"", Some("../../bin/node")),
+			("usr/bin/.wh.removed", b"", None),
+		])?;
+		archive(
+			&path,
+			&[
+				("manifest.json", br#"[{"Layers":["base/layer.tar","top/layer.tar"]}]"#.to_vec()),
+				("base/layer.tar", base),
+				("top/layer.tar", top),
+			],
+		)?;
+
+		let image = ImageArchive::open(&path)?;
+		let dest = temp_dir.path().join("node");
+		image.extract("/usr/local/bin/node", &dest)?;
+		assert_eq!(read(&dest)?, b"new");
+		assert_eq!(dest.metadata()?.permissions().mode() & 0o777, 0o755);
+		assert!(matches!(
+			image.extract("/usr/bin/removed", &temp_dir.path().join("removed")),
+			Err(Error::MissingBinary(_))
+		));
+		Ok(())
+	}
+
+	#[test]
+	fn test_extract_respects_opaque_and_ancestor_whiteouts() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let path = temp_dir.path().join("image.tar");
+		let base = layer(&[
+			("usr/bin/node", b"old", None),
+			("usr/bin/replaced", b"old", None),
+			("opt/app/bin/node", b"old", None),
+		])?;
+		let top = layer(&[
+			("usr/bin/.wh..wh..opq", b"", None),
+			("usr/bin/replaced", b"new", None),
+			("opt/.wh.app", b"", None),
+		])?;
+		archive(
+			&path,
+			&[
+				("manifest.json", br#"[{"Layers":["base/layer.tar","top/layer.tar"]}]"#.to_vec()),
+				("base/layer.tar", base),
+				("top/layer.tar", top),
+			],
+		)?;
+
+		let image = ImageArchive::open(&path)?;
+		let dest = temp_dir.path().join("node");
+		// Files within a directory made opaque are only visible when added by the same layer
+		image.extract("/usr/bin/replaced", &dest)?;
+		assert_eq!(read(&dest)?, b"new");
+		for binary in ["/usr/bin/node", "/opt/app/bin/node"] {
+			assert!(matches!(image.extract(binary, &dest), Err(Error::MissingBinary(_))));
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn test_whiteouts() {
+		assert_eq!(
+			whiteouts("usr/bin"),
+			[".wh..wh..opq", ".wh.usr", "usr/.wh..wh..opq", "usr/.wh.bin"]
+		);
+	}
+
+	#[test]
+	fn test_extract_from_oci_archive() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let path = temp_dir.path().join("image.tar");
+		let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+		std::io::Write::write_all(&mut encoder, &layer(&[("usr/bin/node", b"binary", None)])?)?;
+		archive(
+			&path,
+			&[
+				("index.json", br#"{"manifests":[{"digest":"sha256:abc"}]}"#.to_vec()),
+				("blobs/sha256/abc", br#"{"layers":[{"digest":"sha256:def"}]}"#.to_vec()),
+				("blobs/sha256/def", encoder.finish()?),
+			],
+		)?;
+
+		let dest = temp_dir.path().join("node");
+		ImageArchive::open(&path)?.extract("usr/bin/node", &dest)?;
+		assert_eq!(read(&dest)?, b"binary");
+		Ok(())
+	}
+
+	#[test]
+	fn test_open_fails_not_an_image() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let path = temp_dir.path().join("image.tar");
+		archive(&path, &[("file", b"contents".to_vec())])?;
+		assert!(matches!(
+			ImageArchive::open(&path)?.extract("file", &temp_dir.path().join("file")),
+			Err(Error::Archive(_))
+		));
+		Ok(())
+	}
+}
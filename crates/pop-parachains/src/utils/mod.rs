@@ -1,4 +1,8 @@
 // SPDX-License-Identifier: GPL-3.0
 pub mod git;
 pub mod helpers;
+pub mod image;
 pub mod pallet_helpers;
+pub mod rpc;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
@@ -0,0 +1,27 @@
+// SPDX-License-Identifier: GPL-3.0
+
+/// The HTTP uri of a node's RPC server, from its websocket uri (e.g. `ws://127.0.0.1:9944`), as
+/// both are served from the same port.
+///
+/// # Arguments
+///
+/// * `ws_uri` - the websocket uri of the node
+pub(crate) fn http_uri(ws_uri: &str) -> String {
+	match ws_uri.split_once("://") {
+		Some(("wss" | "https", address)) => format!("https://{address}"),
+		Some((_, address)) => format!("http://{address}"),
+		None => format!("http://{ws_uri}"),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_http_uri() {
+		assert_eq!(http_uri("ws://127.0.0.1:9944"), "http://127.0.0.1:9944");
+		assert_eq!(http_uri("wss://example.com"), "https://example.com");
+		assert_eq!(http_uri("127.0.0.1:9944"), "http://127.0.0.1:9944");
+	}
+}
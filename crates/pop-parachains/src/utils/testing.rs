@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: GPL-3.0
+use serde_json::{json, Value};
+use std::{
+	io::{Read, Write},
+	net::TcpListener,
+	sync::Arc,
+	thread,
+};
+use tungstenite::Message;
+
+/// A response served over HTTP: the status code, any additional headers and the body.
+pub type Response = (u16, Vec<(&'static str, String)>, Vec<u8>);
+
+/// Serves responses from the provided handler over HTTP, which receives the requested path and the
+/// start of any requested range. Returns the url of the server.
+pub fn serve(handler: impl Fn(&str, Option<u64>) -> (u16, Vec<u8>) + Send + 'static) -> String {
+	serve_with_headers(move |path, range| {
+		let (status, body) = handler(path, range);
+		(status, Vec::new(), body)
+	})
+}
+
+/// Serves responses, including headers, from the provided handler over HTTP, which receives the
+/// requested path and the start of any requested range. Returns the url of the server.
+pub fn serve_with_headers(
+	handler: impl Fn(&str, Option<u64>) -> Response + Send + 'static,
+) -> String {
+	let listener = TcpListener::bind("127.0.0.1:0").expect("address available");
+	let url = format!("http://{}", listener.local_addr().expect("bound to address"));
+	thread::spawn(move || {
+		for mut stream in listener.incoming().flatten() {
+			let mut request = Vec::new();
+			let mut buffer = [0; 1024];
+			while !request.ends_with(b"\r\n\r\n") {
+				match stream.read(&mut buffer) {
+					Ok(0) | Err(_) => break,
+					Ok(n) => request.extend_from_slice(&buffer[..n]),
+				}
+			}
+			let request = String::from_utf8_lossy(&request);
+			let path = request.split_whitespace().nth(1).unwrap_or("/");
+			let range = request
+				.lines()
+				.find_map(|l| l.to_lowercase().strip_prefix("range: bytes=").map(String::from))
+				.and_then(|r| r.trim_end_matches('-').parse().ok());
+			let (status, headers, body) = handler(path, range);
+			let headers: String =
+				headers.iter().map(|(name, value)| format!("{name}: {value}\r\n")).collect();
+			let _ = stream.write_all(
+				format!(
+					"HTTP/1.1 {status} Status\r\n{headers}Content-Length: {}\r\nConnection: close\r\n\r\n",
+					body.len()
+				)
+				.as_bytes(),
+			);
+			let _ = stream.write_all(&body);
+		}
+	});
+	url
+}
+
+/// Serves JSON-RPC requests over websockets, with the result of each call provided by the handler
+/// from the method and parameters. Subscriptions (e.g. `chain_subscribeFinalizedHeads`) are
+/// accepted, with each item of the array provided by the handler sent as a notification. Returns
+/// the url of the server.
+pub fn serve_rpc(handler: impl Fn(&str, &Value) -> Value + Send + Sync + 'static) -> String {
+	let listener = TcpListener::bind("127.0.0.1:0").expect("address available");
+	let url = format!("ws://{}", listener.local_addr().expect("bound to address"));
+	let handler = Arc::new(handler);
+	thread::spawn(move || {
+		for stream in listener.incoming().flatten() {
+			let handler = handler.clone();
+			// Connections are held open by clients, so are served concurrently
+			thread::spawn(move || {
+				let Ok(mut socket) = tungstenite::accept(stream) else {
+					return;
+				};
+				while let Ok(message) = socket.read() {
+					let Ok(request) = serde_json::from_slice::<Value>(&message.into_data()) else {
+						continue;
+					};
+					let method = request["method"].as_str().unwrap_or_default();
+					let result = handler(method, &request["params"]);
+					let responses = match method.contains("_subscribe") {
+						true => {
+							const ID: &str = "subscription";
+							let notifications = result.as_array().cloned().unwrap_or_default();
+							[json!({ "jsonrpc": "2.0", "id": request["id"], "result": ID })]
+								.into_iter()
+								.chain(notifications.into_iter().map(|result| {
+									json!({
+										"jsonrpc": "2.0",
+										"method": method,
+										"params": { "subscription": ID, "result": result }
+									})
+								}))
+								.collect()
+						},
+						false =>
+							vec![json!({ "jsonrpc": "2.0", "id": request["id"], "result": result })],
+					};
+					for response in responses {
+						if socket.send(Message::Text(response.to_string())).is_err() {
+							return;
+						}
+					}
+				}
+			});
+		}
+	});
+	url
+}
@@ -0,0 +1,640 @@
+// SPDX-License-Identifier: GPL-3.0
+use crate::{
+	errors::Error,
+	up::{parse_config, Format, SystemParachain},
+};
+use std::{
+	collections::HashMap,
+	fmt::{Display, Formatter},
+	ops::Range,
+	path::Path,
+};
+use toml_edit::{ImDocument, Item, TableLike};
+
+const ROOT_KEYS: [&str; 4] = ["settings", "relaychain", "parachains", "hrmp_channels"];
+const SETTINGS_KEYS: [&str; 12] = [
+	"bootnodes",
+	"timeout",
+	"node_spawn_timeout",
+	"local_ip",
+	"base_dir",
+	"spawn_concurrency",
+	"provider",
+	"enable_tracing",
+	"backchannel",
+	"jaeger_agent",
+	"tracing_collator_url",
+	"tear_down_on_failure",
+];
+const RELAY_CHAIN_KEYS: [&str; 17] = [
+	"chain",
+	"default_command",
+	"default_image",
+	"default_resources",
+	"default_db_snapshot",
+	"default_args",
+	"chain_spec_path",
+	"chain_spec_command",
+	"chain_spec_command_is_local",
+	"random_nominators_count",
+	"max_nominations",
+	"nodes",
+	"node_groups",
+	"genesis",
+	"runtime_genesis_patch",
+	"wasm_override",
+	"command",
+];
+const NODE_KEYS: [&str; 24] = [
+	"name",
+	"image",
+	"command",
+	"subcommand",
+	"args",
+	"validator",
+	"invulnerable",
+	"bootnode",
+	"balance",
+	"env",
+	"bootnodes",
+	"resources",
+	"ws_port",
+	"rpc_port",
+	"prometheus_port",
+	"p2p_port",
+	"p2p_cert_hash",
+	"db_snapshot",
+	"keystore_key_types",
+	"substrate_cli_args_version",
+	"add_to_bootnodes",
+	"count",
+	"full_node_p2p_port",
+	"full_node_prometheus_port",
+];
+const PARACHAIN_KEYS: [&str; 28] = [
+	"id",
+	"chain",
+	"default_command",
+	"default_image",
+	"default_resources",
+	"default_db_snapshot",
+	"default_args",
+	"genesis_wasm_path",
+	"genesis_wasm_generator",
+	"genesis_state_path",
+	"genesis_state_generator",
+	"chain_spec_path",
+	"chain_spec_command",
+	"chain_spec_command_is_local",
+	"cumulus_based",
+	"evm_based",
+	"balance",
+	"add_to_genesis",
+	"register_para",
+	"registration_strategy",
+	"onboard_as_parachain",
+	"bootnodes",
+	"collators",
+	"collator",
+	"collator_groups",
+	"genesis_overrides",
+	"prometheus_prefix",
+	"wasm_override",
+];
+const HRMP_CHANNEL_KEYS: [&str; 4] = ["sender", "recipient", "max_capacity", "max_message_size"];
+
+/// The severity of a diagnostic.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Severity {
+	/// The configuration is likely to behave unexpectedly.
+	Warning,
+	/// The configuration cannot be used to launch a network.
+	Error,
+}
+
+/// An issue identified within a network configuration.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Diagnostic {
+	/// The severity of the issue.
+	pub severity: Severity,
+	/// A description of the issue.
+	pub message: String,
+	/// The line and column of the issue within the configuration, starting from 1.
+	pub location: Option<(usize, usize)>,
+}
+
+impl Display for Diagnostic {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		let severity = match self.severity {
+			Severity::Warning => "warning",
+			Severity::Error => "error",
+		};
+		match self.location {
+			Some((line, column)) => write!(f, "{severity} [{line}:{column}]: {}", self.message),
+			None => write!(f, "{severity}: {}", self.message),
+		}
+	}
+}
+
+/// Validates a network configuration file, returning all identified issues. Issues within JSON
+/// and YAML configurations are reported without locations.
+///
+/// # Arguments
+///
+/// * `path` - the path to the network configuration file
+pub fn validate(path: &Path) -> Result<Vec<Diagnostic>, Error> {
+	let contents = std::fs::read_to_string(path)?;
+	if Format::detect(path, &contents) == Format::Toml {
+		return Ok(validate_toml(&contents));
+	}
+	let mut validator = Validator::new("");
+	match parse_config(path, &contents) {
+		Ok(document) => validator.validate(document.as_table()),
+		Err(e) => validator.error(e.to_string(), None),
+	}
+	Ok(validator.diagnostics)
+}
+
+/// Validates the contents of a network configuration, returning all identified issues ordered
+/// by their location.
+///
+/// # Arguments
+///
+/// * `contents` - the network configuration, as TOML
+pub fn validate_toml(contents: &str) -> Vec<Diagnostic> {
+	let document = match contents.parse::<ImDocument<String>>() {
+		Ok(document) => document,
+		Err(e) => {
+			let mut validator = Validator::new(contents);
+			validator.error(e.message().trim().to_string(), e.span());
+			return validator.diagnostics;
+		},
+	};
+	let mut validator = Validator::new(contents);
+	validator.validate(document.as_table());
+	validator
+		.diagnostics
+		.sort_by_key(|d| d.location.unwrap_or((usize::MAX, usize::MAX)));
+	validator.diagnostics
+}
+
+/// Whether any of the diagnostics is an error.
+///
+/// # Arguments
+///
+/// * `diagnostics` - the diagnostics to check
+pub fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+	diagnostics.iter().any(|d| d.severity == Severity::Error)
+}
+
+struct Validator<'a> {
+	contents: &'a str,
+	diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Validator<'a> {
+	fn new(contents: &'a str) -> Self {
+		Self { contents, diagnostics: Vec::new() }
+	}
+
+	fn validate(&mut self, root: &dyn TableLike) {
+		self.unknown_keys(root, &ROOT_KEYS, "the network configuration");
+		if let Some(settings) = root.get("settings").and_then(|s| s.as_table_like()) {
+			self.unknown_keys(settings, &SETTINGS_KEYS, "`settings`");
+		}
+
+		// Node names must be unique across the whole network
+		let mut names: HashMap<String, Option<Range<usize>>> = HashMap::new();
+
+		// Relay chain
+		let mut validators = 0;
+		match root.get("relaychain").and_then(|r| r.as_table_like()) {
+			None => self.error("expected `relaychain`".into(), None),
+			Some(relay_chain) => {
+				self.unknown_keys(relay_chain, &RELAY_CHAIN_KEYS, "`relaychain`");
+				self.string(relay_chain, "chain", "`relaychain`");
+				self.string(relay_chain, "default_command", "`relaychain`");
+				for (span, node) in tables(relay_chain.get("nodes")) {
+					self.unknown_keys(node, &NODE_KEYS, "`relaychain.nodes`");
+					self.node_name(node, span, "relay chain node", &mut names);
+					if node.get("validator").and_then(|v| v.as_bool()).unwrap_or(true) {
+						validators += 1;
+					}
+				}
+				for (span, group) in tables(relay_chain.get("node_groups")) {
+					self.unknown_keys(group, &NODE_KEYS, "`relaychain.node_groups`");
+					self.node_name(group, span, "relay chain node group", &mut names);
+					let count = group.get("count").and_then(|c| c.as_integer()).unwrap_or(1);
+					if group.get("validator").and_then(|v| v.as_bool()).unwrap_or(true) {
+						validators += count.max(0) as usize;
+					}
+				}
+				if validators == 0 {
+					self.error(
+						"the relay chain requires at least one validator".into(),
+						self.key_span(relay_chain, "nodes").or(self.key_span(root, "relaychain")),
+					);
+				}
+			},
+		}
+
+		// Parachains
+		let mut para_ids: HashMap<i64, Option<Range<usize>>> = HashMap::new();
+		let parachains = tables(root.get("parachains"));
+		for (span, parachain) in &parachains {
+			self.unknown_keys(*parachain, &PARACHAIN_KEYS, "`parachains`");
+			let chain = parachain.get("chain").and_then(|c| c.as_str());
+			let id = match parachain.get("id") {
+				// System parachains default to their well-known identifier
+				None => match chain.and_then(SystemParachain::find) {
+					Some(parachain) => Some(parachain.id as i64),
+					None => {
+						self.error("expected `parachain` to have `id`".into(), span.clone());
+						None
+					},
+				},
+				Some(id) => match id.as_integer() {
+					Some(id) => Some(id),
+					None => {
+						self.error(
+							format!("expected `id` to be an integer, found {}", id.type_name()),
+							self.key_span(*parachain, "id"),
+						);
+						None
+					},
+				},
+			};
+			let label = id.map_or("parachain".to_string(), |id| format!("parachain {id}"));
+			if let Some(id) = id {
+				let location = self.key_span(*parachain, "id").or(span.clone());
+				match para_ids.get(&id) {
+					Some(existing) => self.error(
+						format!(
+							"duplicate parachain id {id}, already defined{}",
+							self.at(existing.clone())
+						),
+						location,
+					),
+					None => {
+						para_ids.insert(id, location);
+					},
+				}
+			}
+			let default_command = self.string(*parachain, "default_command", &label);
+
+			let mut collators = tables(parachain.get("collators"));
+			if let Some(collator) = parachain.get("collator").and_then(|c| c.as_table_like()) {
+				collators.push((self.key_span(*parachain, "collator"), collator));
+			}
+			let groups = tables(parachain.get("collator_groups"));
+			if collators.is_empty() && groups.is_empty() {
+				self.error(format!("{label} does not define any collators"), span.clone());
+			}
+			for (span, collator) in collators.iter().chain(groups.iter()) {
+				self.unknown_keys(*collator, &NODE_KEYS, &format!("the collators of {label}"));
+				let name = self.node_name(*collator, span.clone(), "collator", &mut names);
+				let command = self.string(*collator, "command", &label);
+				if command.is_none() && default_command.is_none() && chain.is_none() {
+					self.warning(
+						format!(
+							"collator{} of {label} has no `command` and {label} defines neither `default_command` nor `chain`, so `polkadot-parachain` will be used",
+							name.map_or(String::new(), |n| format!(" `{n}`"))
+						),
+						span.clone(),
+					);
+				}
+			}
+		}
+		if !parachains.is_empty() && validators > 0 && validators < parachains.len() {
+			self.error(
+				format!(
+					"the relay chain has {validators} validator(s), which is too few for {} parachains: each parachain requires at least one validator",
+					parachains.len()
+				),
+				root.get("relaychain")
+					.and_then(|r| r.as_table_like())
+					.and_then(|r| self.key_span(r, "nodes"))
+					.or(self.key_span(root, "relaychain")),
+			);
+		}
+
+		// HRMP channels
+		for (span, channel) in tables(root.get("hrmp_channels")) {
+			self.unknown_keys(channel, &HRMP_CHANNEL_KEYS, "`hrmp_channels`");
+			for key in ["sender", "recipient"] {
+				match channel.get(key).and_then(|i| i.as_integer()) {
+					None => self.error(
+						format!("expected `hrmp_channels` to have integer `{key}`"),
+						span.clone(),
+					),
+					Some(id) if !para_ids.contains_key(&id) => self.error(
+						format!("the hrmp channel {key} {id} is not a parachain of the network"),
+						self.key_span(channel, key),
+					),
+					_ => {},
+				}
+			}
+		}
+	}
+
+	// Checks that a node has a unique name, returning the name if valid.
+	fn node_name(
+		&mut self,
+		node: &dyn TableLike,
+		span: Option<Range<usize>>,
+		kind: &str,
+		names: &mut HashMap<String, Option<Range<usize>>>,
+	) -> Option<String> {
+		let Some(name) = node.get("name").and_then(|n| n.as_str()) else {
+			self.error(format!("expected {kind} to have `name`"), span);
+			return None;
+		};
+		let location = self.key_span(node, "name");
+		match names.get(name) {
+			Some(existing) => self.error(
+				format!(
+					"duplicate node name `{name}`, already defined{}",
+					self.at(existing.clone())
+				),
+				location,
+			),
+			None => {
+				names.insert(name.to_string(), location);
+			},
+		}
+		Some(name.to_string())
+	}
+
+	// Checks that a key, if present, is a string, returning its value.
+	fn string<'t>(
+		&mut self,
+		table: &'t dyn TableLike,
+		key: &str,
+		context: &str,
+	) -> Option<&'t str> {
+		let item = table.get(key)?;
+		match item.as_str() {
+			Some(value) => Some(value),
+			None => {
+				self.error(
+					format!(
+						"expected `{key}` of {context} to be a string, found {}",
+						item.type_name()
+					),
+					self.key_span(table, key),
+				);
+				None
+			},
+		}
+	}
+
+	fn unknown_keys(&mut self, table: &dyn TableLike, known: &[&str], context: &str) {
+		for (key, _) in table.iter() {
+			if !known.contains(&key) {
+				self.warning(
+					format!("unknown key `{key}` in {context}"),
+					self.key_span(table, key),
+				);
+			}
+		}
+	}
+
+	fn key_span(&self, table: &dyn TableLike, key: &str) -> Option<Range<usize>> {
+		table.get_key_value(key).and_then(|(k, _)| k.span())
+	}
+
+	fn at(&self, span: Option<Range<usize>>) -> String {
+		match self.location(span) {
+			Some((line, column)) => format!(" at {line}:{column}"),
+			None => String::new(),
+		}
+	}
+
+	fn location(&self, span: Option<Range<usize>>) -> Option<(usize, usize)> {
+		let offset = span?.start.min(self.contents.len());
+		let preceding = &self.contents[..offset];
+		let line = preceding.matches('\n').count() + 1;
+		let column = preceding.rsplit('\n').next().map_or(0, |l| l.chars().count()) + 1;
+		Some((line, column))
+	}
+
+	fn error(&mut self, message: String, span: Option<Range<usize>>) {
+		let location = self.location(span);
+		self.diagnostics
+			.push(Diagnostic { severity: Severity::Error, message, location });
+	}
+
+	fn warning(&mut self, message: String, span: Option<Range<usize>>) {
+		let location = self.location(span);
+		self.diagnostics
+			.push(Diagnostic { severity: Severity::Warning, message, location });
+	}
+}
+
+// Returns the tables of an array of tables or an array of inline tables, along with their
+// locations.
+fn tables(item: Option<&Item>) -> Vec<(Option<Range<usize>>, &dyn TableLike)> {
+	match item {
+		Some(Item::ArrayOfTables(tables)) =>
+			tables.iter().map(|t| (t.span(), t as &dyn TableLike)).collect(),
+		Some(Item::Value(value)) => value
+			.as_array()
+			.map(|array| {
+				array
+					.iter()
+					.filter_map(|v| v.as_inline_table().map(|t| (v.span(), t as &dyn TableLike)))
+					.collect()
+			})
+			.unwrap_or_default(),
+		_ => Vec::new(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+	use std::path::PathBuf;
+
+	const CONFIG_FILE_PATH: &str = "../../tests/zombienet.toml";
+
+	fn errors(diagnostics: &[Diagnostic]) -> Vec<String> {
+		diagnostics
+			.iter()
+			.filter(|d| d.severity == Severity::Error)
+			.map(|d| d.to_string())
+			.collect()
+	}
+
+	#[test]
+	fn test_validate_valid_config() -> Result<()> {
+		let diagnostics = validate(&PathBuf::from(CONFIG_FILE_PATH))?;
+		assert_eq!(diagnostics, vec![]);
+		assert!(!has_errors(&diagnostics));
+		Ok(())
+	}
+
+	#[test]
+	fn test_validate_json_and_yaml_configs() -> Result<()> {
+		for config in ["../../tests/zombienet.json", "../../tests/zombienet.yaml"] {
+			assert_eq!(validate(&PathBuf::from(config))?, vec![]);
+		}
+
+		let temp_dir = tempfile::tempdir()?;
+		let config = temp_dir.path().join("network.json");
+		std::fs::write(
+			&config,
+			r#"{ "relaychain": { "chain": "rococo-local", "nodes": [{ "name": "alice" }] },
+			"parachains": [{ "collators": [{ "name": "alice" }] }] }"#,
+		)?;
+		assert_eq!(
+			errors(&validate(&config)?),
+			vec![
+				"error: expected `parachain` to have `id`",
+				"error: duplicate node name `alice`, already defined",
+			]
+		);
+
+		std::fs::write(&config, "{ \"relaychain\": ")?;
+		let diagnostics = validate(&config)?;
+		assert_eq!(diagnostics.len(), 1);
+		assert_eq!(diagnostics[0].severity, Severity::Error);
+		assert_eq!(diagnostics[0].location, None);
+		Ok(())
+	}
+
+	#[test]
+	fn test_validate_reports_parse_error() {
+		let diagnostics = validate_toml("[relaychain\nchain = \"rococo-local\"");
+		assert_eq!(diagnostics.len(), 1);
+		assert_eq!(diagnostics[0].severity, Severity::Error);
+		assert_eq!(diagnostics[0].location, Some((1, 12)));
+	}
+
+	#[test]
+	fn test_validate_reports_all_issues() {
+		let diagnostics = validate_toml(
+			r#"[relaychain]
+chain = "rococo-local"
+
+[[relaychain.nodes]]
+name = "alice"
+validator = true
+
+[[parachains]]
+id = 2000
+default_command = "./target/release/parachain"
+
+[[parachains.collators]]
+name = "alice"
+
+[[parachains]]
+id = 2000
+colators = []
+
+[[parachains]]
+chain = "./chain-spec.json"
+
+[[parachains.collators]]
+name = "asset-hub"
+"#,
+		);
+		assert_eq!(
+			errors(&diagnostics),
+			vec![
+				"error [4:14]: the relay chain has 1 validator(s), which is too few for 3 parachains: each parachain requires at least one validator",
+				"error [13:1]: duplicate node name `alice`, already defined at 5:1",
+				"error [15:1]: parachain 2000 does not define any collators",
+				"error [16:1]: duplicate parachain id 2000, already defined at 9:1",
+				"error [19:1]: expected `parachain` to have `id`",
+			]
+		);
+		assert!(diagnostics.contains(&Diagnostic {
+			severity: Severity::Warning,
+			message: "unknown key `colators` in `parachains`".into(),
+			location: Some((17, 1)),
+		}));
+		assert!(has_errors(&diagnostics));
+	}
+
+	#[test]
+	fn test_validate_missing_relay_chain() {
+		let diagnostics = validate_toml(
+			r#"[[parachains]]
+id = 1000
+chain = "asset-hub-rococo-local"
+
+[[parachains.collators]]
+name = "asset-hub"
+"#,
+		);
+		assert_eq!(errors(&diagnostics), vec!["error: expected `relaychain`"]);
+	}
+
+	#[test]
+	fn test_validate_system_parachain_default_id() {
+		let diagnostics = validate_toml(
+			r#"[relaychain]
+chain = "rococo-local"
+
+[[relaychain.nodes]]
+name = "alice"
+validator = true
+
+[[relaychain.nodes]]
+name = "bob"
+validator = true
+
+[[parachains]]
+chain = "coretime-rococo-local"
+
+[[parachains.collators]]
+name = "coretime"
+
+[[parachains]]
+id = 1005
+
+[[parachains.collators]]
+name = "collator"
+command = "./node"
+
+[[hrmp_channels]]
+sender = 1005
+recipient = 1005
+max_capacity = 8
+max_message_size = 512
+"#,
+		);
+		// The chain is assigned its default id, which conflicts with that specified
+		assert_eq!(
+			errors(&diagnostics),
+			vec!["error [19:1]: duplicate parachain id 1005, already defined at 12:1"]
+		);
+	}
+
+	#[test]
+	fn test_validate_collator_without_command() {
+		let diagnostics = validate_toml(
+			r#"[relaychain]
+chain = "rococo-local"
+default_command = 1
+
+[[relaychain.nodes]]
+name = "alice"
+
+[[parachains]]
+id = 2000
+
+[[parachains.collators]]
+name = "collator"
+"#,
+		);
+		assert_eq!(
+			diagnostics.iter().map(|d| d.to_string()).collect::<Vec<_>>(),
+			vec![
+				"error [3:1]: expected `default_command` of `relaychain` to be a string, found integer",
+				"warning [11:1]: collator `collator` of parachain 2000 has no `command` and parachain 2000 defines neither `default_command` nor `chain`, so `polkadot-parachain` will be used"
+			]
+		);
+	}
+}
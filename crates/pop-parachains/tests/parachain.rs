@@ -20,6 +20,7 @@ async fn test_spawn_polkadot_and_two_parachains() -> Result<()> {
 		Some(&TESTING_POLKADOT_VERSION.to_string()),
 		Some(&TESTING_POLKADOT_VERSION.to_string()),
 		Some(&vec!["https://github.com/r0gue-io/pop-node".to_string()]),
+		false,
 	)
 	.await?;
 	let missing_binaries = zombienet.missing_binaries();